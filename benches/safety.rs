@@ -0,0 +1,173 @@
+//! Compares the naive safety loop with the incremental checker.
+//!
+//! Run with `cargo bench --bench safety`. Every state is checked by both, and
+//! the run stops if their verdicts ever differ.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bankers_algo::{BankersAlgorithm, Process};
+
+/// Small linear congruential generator, enough to spread the test states.
+struct Lcg(u64);
+
+impl Lcg {
+    fn up_to(&mut self, max: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) % (max + 1)
+    }
+}
+
+/// `num_processes` processes holding a few units each, with `slack` free
+/// units per resource type. Little slack tends to give unsafe states.
+fn random_state(
+    rng: &mut Lcg,
+    num_processes: usize,
+    num_resources: usize,
+    slack: u64,
+) -> BankersAlgorithm {
+    let mut totals = vec![slack; num_resources];
+    let mut parts = Vec::with_capacity(num_processes);
+    for _ in 0..num_processes {
+        let allocation: Vec<u64> = (0..num_resources).map(|_| rng.up_to(3)).collect();
+        let max_need: Vec<u64> = allocation
+            .iter()
+            .map(|&units| units + rng.up_to(4))
+            .collect();
+        for (total, &units) in totals.iter_mut().zip(&allocation) {
+            *total += units;
+        }
+        parts.push((allocation, max_need));
+    }
+    build(totals, parts)
+}
+
+/// A state where the naive loop finishes only one process per pass: each
+/// process needs one unit more than the one after it.
+fn reverse_chain(num_processes: usize) -> BankersAlgorithm {
+    let n = num_processes as u64;
+    let parts = (0..n).map(|i| (vec![1], vec![1 + n - i])).collect();
+    build(vec![n + 1], parts)
+}
+
+fn build(totals: Vec<u64>, parts: Vec<(Vec<u64>, Vec<u64>)>) -> BankersAlgorithm {
+    let totals_max = totals.clone();
+    let processes = parts
+        .into_iter()
+        .enumerate()
+        .map(|(id, (allocation, max_need))| {
+            let max_need = max_need
+                .iter()
+                .zip(&totals_max)
+                .map(|(&max, &total)| max.min(total))
+                .collect();
+            Process::new(id, allocation, max_need).expect("allocation fits the claim")
+        })
+        .collect();
+    BankersAlgorithm::from_parts(totals, processes).expect("state is consistent")
+}
+
+/// Checks both verdicts agree and that the fast checker's sequence really
+/// lets every process finish.
+fn verify(state: &BankersAlgorithm) -> bool {
+    let naive = state.is_safe_state();
+    let fast = state.is_safe_state_fast();
+    assert_eq!(
+        naive.is_some(),
+        fast.is_some(),
+        "verdicts differ for {:?}",
+        state
+    );
+
+    if let Some(sequence) = &fast {
+        let mut work = state.available().to_vec();
+        for &pid in sequence {
+            let process = state.process(pid).expect("sequence lists known processes");
+            assert!(
+                process.need().iter().zip(&work).all(|(n, w)| n <= w),
+                "P{} cannot finish in {:?} for {:?}",
+                pid,
+                sequence,
+                state
+            );
+            for (w, a) in work.iter_mut().zip(process.allocation()) {
+                *w += a;
+            }
+        }
+        assert_eq!(sequence.len(), state.processes().len());
+    }
+    fast.is_some()
+}
+
+fn time<T>(runs: u32, mut check: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(check());
+    }
+    start.elapsed() / runs
+}
+
+fn compare(name: &str, states: &[BankersAlgorithm], runs: u32) {
+    let safe = states.iter().filter(|state| verify(state)).count();
+
+    let naive = time(runs, || {
+        states
+            .iter()
+            .filter(|s| s.is_safe_state().is_some())
+            .count()
+    });
+    let fast = time(runs, || {
+        states
+            .iter()
+            .filter(|s| s.is_safe_state_fast().is_some())
+            .count()
+    });
+    println!(
+        "{:<28} {:>3}/{:<3} safe  naive {:>12.3?}  fast {:>12.3?}  x{:.1}",
+        name,
+        safe,
+        states.len(),
+        naive / states.len() as u32,
+        fast / states.len() as u32,
+        naive.as_secs_f64() / fast.as_secs_f64()
+    );
+}
+
+fn main() {
+    let mut rng = Lcg(42);
+
+    // Many small states first, so that disagreements show up before the
+    // timings. The chains need more passes than the fast checker makes
+    // before it switches to its worklist.
+    for _ in 0..20_000 {
+        let num_processes = rng.up_to(8) as usize;
+        let num_resources = 1 + rng.up_to(3) as usize;
+        let slack = rng.up_to(4);
+        verify(&random_state(&mut rng, num_processes, num_resources, slack));
+    }
+    for num_processes in 0..200 {
+        verify(&reverse_chain(num_processes));
+    }
+    println!("20000 small random states and 200 chains: verdicts agree");
+
+    for &(num_processes, num_resources, runs) in &[(100, 4, 50), (1_000, 8, 5), (4_000, 8, 1)] {
+        let states: Vec<BankersAlgorithm> = (0..20)
+            .map(|i| random_state(&mut rng, num_processes, num_resources, i % 5))
+            .collect();
+        compare(
+            &format!("random n={} m={}", num_processes, num_resources),
+            &states,
+            runs,
+        );
+    }
+    for &num_processes in &[1_000, 4_000] {
+        compare(
+            &format!("reverse chain n={}", num_processes),
+            &[reverse_chain(num_processes)],
+            3,
+        );
+    }
+}
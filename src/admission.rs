@@ -0,0 +1,268 @@
+use crate::{BankersAlgorithm, BankersError, Denial, Process};
+
+impl BankersAlgorithm {
+    /// Admits a new process with `max_need` as its claim and no allocation,
+    /// returning its id. Ids are handed out in increasing order and never
+    /// reused.
+    ///
+    /// The claim must fit within the totals, and every process, the newcomer
+    /// included, must still be able to finish.
+    pub fn admit(&mut self, max_need: &[u64]) -> Result<usize, Denial> {
+        let pid = self.next_pid.ok_or(BankersError::ProcessIdsExhausted)?;
+        self.check_len(pid, max_need)?;
+        self.check_claim(pid, max_need)?;
+
+        let process = Process::new(pid, vec![0; max_need.len()], max_need.to_vec())?;
+        self.processes.push(process);
+        if self.is_safe_state_fast().is_none() {
+            self.processes.pop();
+            return Err(Denial::ClaimUnsafe { pid });
+        }
+        self.next_pid = pid.checked_add(1);
+        Ok(pid)
+    }
+
+    /// Adds a process that already holds `allocation`, e.g. one that was
+    /// running before the banker took over, and returns its id.
+    ///
+    /// Unlike [`admit`](Self::admit) this does not check safety. The
+    /// allocation must be available and the claim must fit within the totals.
+    pub fn add_process(
+        &mut self,
+        allocation: &[u64],
+        max_need: &[u64],
+    ) -> Result<usize, BankersError> {
+        let pid = self.next_pid.ok_or(BankersError::ProcessIdsExhausted)?;
+        self.check_len(pid, allocation)?;
+        self.check_len(pid, max_need)?;
+        let process = Process::new(pid, allocation.to_vec(), max_need.to_vec())?;
+        self.check_claim(pid, max_need)?;
+        for (resource, (&units, &available)) in allocation.iter().zip(&self.available).enumerate() {
+            if units > available {
+                let total = self.resources[resource];
+                return Err(BankersError::OverAllocated {
+                    pid,
+                    resource,
+                    allocated: (total - available).saturating_add(units),
+                    total,
+                });
+            }
+        }
+
+        for (available, &units) in self.available.iter_mut().zip(allocation) {
+            *available -= units;
+        }
+        self.processes.push(process);
+        self.next_pid = pid.checked_add(1);
+        Ok(pid)
+    }
+
+    /// Raises the maximum claim of process `pid` by `additional`, for
+    /// processes that learn what they need as they go.
+    ///
+    /// Refused if the claim would exceed the totals or leave the state unsafe.
+    pub fn declare_claim(&mut self, pid: usize, additional: &[u64]) -> Result<(), Denial> {
+        let index = self.index_of(pid)?;
+        self.check_len(pid, additional)?;
+
+        let max_need: Vec<u64> = self.processes[index]
+            .max_need
+            .iter()
+            .zip(additional)
+            .map(|(&max, &units)| max.saturating_add(units))
+            .collect();
+        self.revise_max(pid, &max_need)
+    }
+
+    /// Replaces the maximum claim of process `pid` with `new_max` and
+    /// recomputes its need.
+    ///
+    /// A claim can be lowered as far as the current allocation. Raising any
+    /// part of it must fit within the totals and keep the state safe.
+    pub fn revise_max(&mut self, pid: usize, new_max: &[u64]) -> Result<(), Denial> {
+        let index = self.index_of(pid)?;
+        self.check_len(pid, new_max)?;
+        self.check_claim(pid, new_max)?;
+
+        let revised = Process::new(
+            pid,
+            self.processes[index].allocation.clone(),
+            new_max.to_vec(),
+        )?;
+        let raised = revised
+            .max_need
+            .iter()
+            .zip(&self.processes[index].max_need)
+            .any(|(new, old)| new > old);
+
+        let previous = std::mem::replace(&mut self.processes[index], revised);
+        if raised && self.is_safe_state_fast().is_none() {
+            self.processes[index] = previous;
+            return Err(Denial::ClaimUnsafe { pid });
+        }
+        Ok(())
+    }
+
+    fn check_claim(&self, pid: usize, max_need: &[u64]) -> Result<(), BankersError> {
+        for (resource, (&max, &total)) in max_need.iter().zip(&self.resources).enumerate() {
+            if max > total {
+                return Err(BankersError::MaxExceedsTotal {
+                    pid,
+                    resource,
+                    max,
+                    total,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{state, textbook};
+
+    #[test]
+    fn admit_hands_out_fresh_ids() {
+        let mut banker = state(&[4], &[(&[1], &[2]), (&[1], &[2])]);
+        banker.finish(1).unwrap();
+        assert_eq!(banker.admit(&[2]), Ok(2));
+        assert_eq!(banker.process(2).unwrap().need(), [2]);
+
+        banker.finish(2).unwrap();
+        assert_eq!(banker.admit(&[1]), Ok(3));
+        assert_eq!(banker.next_pid(), Some(4));
+    }
+
+    #[test]
+    fn ids_run_out_after_the_largest() {
+        let process = Process::new(usize::MAX - 1, vec![0], vec![1]).unwrap();
+        let mut banker = BankersAlgorithm::from_parts(vec![2], vec![process]).unwrap();
+        assert_eq!(banker.admit(&[1]), Ok(usize::MAX));
+        assert_eq!(banker.next_pid(), None);
+
+        let before = banker.clone();
+        assert_eq!(
+            banker.admit(&[1]),
+            Err(Denial::Invalid(BankersError::ProcessIdsExhausted))
+        );
+        assert_eq!(
+            banker.add_process(&[0], &[1]),
+            Err(BankersError::ProcessIdsExhausted)
+        );
+        assert_eq!(banker, before);
+
+        // A state whose last process already has the largest id still loads.
+        let process = Process::new(usize::MAX, vec![1], vec![1]).unwrap();
+        let banker = BankersAlgorithm::from_parts(vec![2], vec![process]).unwrap();
+        assert_eq!(banker.next_pid(), None);
+    }
+
+    #[test]
+    fn admit_checks_the_claim() {
+        let mut banker = textbook();
+        assert_eq!(
+            banker.admit(&[11, 0, 0]),
+            Err(Denial::Invalid(BankersError::MaxExceedsTotal {
+                pid: 5,
+                resource: 0,
+                max: 11,
+                total: 10,
+            }))
+        );
+        assert_eq!(banker.admit(&[10, 5, 7]), Ok(5));
+    }
+
+    #[test]
+    fn admit_refuses_to_join_an_unsafe_state() {
+        let mut banker = state(&[2], &[(&[1], &[2]), (&[1], &[2])]);
+        let before = banker.clone();
+        assert_eq!(banker.admit(&[1]), Err(Denial::ClaimUnsafe { pid: 2 }));
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn add_process_takes_units_without_checking_safety() {
+        let mut banker = state(&[4], &[(&[1], &[3])]);
+        assert_eq!(banker.add_process(&[2], &[4]), Ok(1));
+        assert_eq!(banker.available(), [1]);
+        assert!(banker.is_safe_state().is_none());
+
+        let before = banker.clone();
+        assert_eq!(
+            banker.add_process(&[2], &[2]),
+            Err(BankersError::OverAllocated {
+                pid: 2,
+                resource: 0,
+                allocated: 5,
+                total: 4,
+            })
+        );
+        assert_eq!(
+            banker.add_process(&[1], &[0]),
+            Err(BankersError::AllocationExceedsMax {
+                pid: 2,
+                resource: 0,
+                allocation: 1,
+                max: 0,
+            })
+        );
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn revise_max_cannot_drop_below_the_allocation() {
+        let mut banker = state(&[4], &[(&[2], &[3]), (&[1], &[2])]);
+        assert_eq!(
+            banker.revise_max(0, &[1]),
+            Err(Denial::Invalid(BankersError::AllocationExceedsMax {
+                pid: 0,
+                resource: 0,
+                allocation: 2,
+                max: 1,
+            }))
+        );
+        assert_eq!(banker.process(0).unwrap().max_need(), [3]);
+    }
+
+    #[test]
+    fn lowering_a_claim_is_always_allowed() {
+        let mut banker = state(&[2], &[(&[1], &[2]), (&[1], &[2])]);
+        assert!(banker.is_safe_state().is_none());
+
+        banker.revise_max(0, &[1]).unwrap();
+        assert_eq!(banker.process(0).unwrap().need(), [0]);
+        assert!(banker.is_safe_state().is_some());
+    }
+
+    #[test]
+    fn unsafe_raise_is_rolled_back() {
+        let mut banker = state(&[4], &[(&[2], &[3]), (&[1], &[2])]);
+        banker.revise_max(1, &[4]).unwrap();
+        assert_eq!(banker.process(1).unwrap().need(), [3]);
+
+        let before = banker.clone();
+        assert_eq!(
+            banker.revise_max(0, &[4]),
+            Err(Denial::ClaimUnsafe { pid: 0 })
+        );
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn declare_claim_adds_to_the_maximum() {
+        let mut banker = state(&[4], &[(&[2], &[3]), (&[1], &[2])]);
+        banker.declare_claim(1, &[1]).unwrap();
+        assert_eq!(banker.process(1).unwrap().max_need(), [3]);
+        assert_eq!(
+            banker.declare_claim(1, &[2]),
+            Err(Denial::Invalid(BankersError::MaxExceedsTotal {
+                pid: 1,
+                resource: 0,
+                max: 5,
+                total: 4,
+            }))
+        );
+    }
+}
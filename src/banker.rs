@@ -0,0 +1,133 @@
+use crate::{BankersError, PendingRequest, Process, QueuePolicy, Wakeup};
+
+/// Total resources, the units still available, the processes holding the
+/// rest, and the requests waiting for units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BankersAlgorithm {
+    pub(crate) available: Vec<u64>,
+    pub(crate) resources: Vec<u64>,
+    pub(crate) processes: Vec<Process>,
+    pub(crate) pending: Vec<PendingRequest>,
+    pub(crate) queue_policy: QueuePolicy,
+    pub(crate) next_ticket: u64,
+    pub(crate) next_pid: Option<usize>,
+    /// Queued requests that left the queue, kept while a
+    /// [`SharedBanker`](crate::SharedBanker) needs to hand them out.
+    pub(crate) woken: Option<Vec<Wakeup>>,
+    /// Set while a [`SharedBanker`](crate::SharedBanker) has units out on
+    /// permits, whose amounts must keep lining up with the resource types.
+    pub(crate) layout_locked: bool,
+}
+
+impl BankersAlgorithm {
+    /// Builds a system state, checking every process against the totals.
+    pub fn from_parts(
+        resources: Vec<u64>,
+        processes: Vec<Process>,
+    ) -> Result<BankersAlgorithm, BankersError> {
+        if resources.is_empty() {
+            return Err(BankersError::NoResources);
+        }
+
+        let num_resources = resources.len();
+        let mut total_allocated = vec![0u64; num_resources];
+
+        for (index, process) in processes.iter().enumerate() {
+            if processes[..index].iter().any(|p| p.id == process.id) {
+                return Err(BankersError::DuplicateProcess(process.id));
+            }
+            if process.allocation.len() != num_resources {
+                return Err(BankersError::ResourceCountMismatch {
+                    pid: process.id,
+                    expected: num_resources,
+                    found: process.allocation.len(),
+                });
+            }
+
+            for i in 0..num_resources {
+                if process.max_need[i] > resources[i] {
+                    return Err(BankersError::MaxExceedsTotal {
+                        pid: process.id,
+                        resource: i,
+                        max: process.max_need[i],
+                        total: resources[i],
+                    });
+                }
+                total_allocated[i] = match total_allocated[i].checked_add(process.allocation[i]) {
+                    Some(allocated) if allocated <= resources[i] => allocated,
+                    sum => {
+                        return Err(BankersError::OverAllocated {
+                            pid: process.id,
+                            resource: i,
+                            allocated: sum.unwrap_or(u64::MAX),
+                            total: resources[i],
+                        });
+                    }
+                };
+            }
+        }
+
+        let available: Vec<u64> = resources
+            .iter()
+            .zip(&total_allocated)
+            .map(|(&total, &allocated)| total - allocated)
+            .collect();
+
+        let next_pid = match processes.iter().map(|p| p.id).max() {
+            Some(last) => last.checked_add(1),
+            None => Some(0),
+        };
+        Ok(BankersAlgorithm {
+            available,
+            resources,
+            processes,
+            pending: Vec::new(),
+            queue_policy: QueuePolicy::default(),
+            next_ticket: 0,
+            next_pid,
+            woken: None,
+            layout_locked: false,
+        })
+    }
+
+    pub fn resources(&self) -> &[u64] {
+        &self.resources
+    }
+
+    pub fn available(&self) -> &[u64] {
+        &self.available
+    }
+
+    pub fn processes(&self) -> &[Process] {
+        &self.processes
+    }
+
+    /// Id of the next process to join. Ids are never reused, so a finished
+    /// process cannot be mistaken for a newcomer. `None` once the last id
+    /// has been handed out.
+    pub fn next_pid(&self) -> Option<usize> {
+        self.next_pid
+    }
+
+    pub fn process(&self, pid: usize) -> Option<&Process> {
+        self.processes.iter().find(|p| p.id == pid)
+    }
+
+    pub(crate) fn index_of(&self, pid: usize) -> Result<usize, BankersError> {
+        self.processes
+            .iter()
+            .position(|p| p.id == pid)
+            .ok_or(BankersError::UnknownProcess(pid))
+    }
+
+    pub(crate) fn check_len(&self, pid: usize, values: &[u64]) -> Result<(), BankersError> {
+        if values.len() != self.resources.len() {
+            return Err(BankersError::ResourceCountMismatch {
+                pid,
+                expected: self.resources.len(),
+                found: values.len(),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,394 @@
+use crate::{BankersAlgorithm, BankersError, Denial, Wakeup};
+
+/// A resource type taken out of the system by
+/// [`retire_resource_type`](BankersAlgorithm::retire_resource_type).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retirement {
+    pub resource: usize,
+    pub units: u64,
+    /// Queued requests for units of the retired type, refused since they
+    /// can never be granted.
+    pub refused: Vec<Wakeup>,
+}
+
+impl BankersAlgorithm {
+    /// Adds `units` of `resource` to the system, e.g. when a node joins.
+    ///
+    /// More capacity never makes a safe state unsafe, so this only checks
+    /// that the resource exists and the count does not overflow.
+    pub fn add_capacity(&mut self, resource: usize, units: u64) -> Result<(), BankersError> {
+        let total = *self
+            .resources
+            .get(resource)
+            .ok_or(BankersError::UnknownResource(resource))?;
+        let new_total = total
+            .checked_add(units)
+            .ok_or(BankersError::CapacityOverflow {
+                resource,
+                total,
+                added: units,
+            })?;
+
+        self.resources[resource] = new_total;
+        self.available[resource] += units;
+        Ok(())
+    }
+
+    /// Removes `units` of `resource` from the system, e.g. when a disk goes offline.
+    ///
+    /// Only units that no process holds can be removed. If the state would
+    /// become unsafe the change is refused with the endangered processes,
+    /// unless `force` is set. A forced removal cuts claims that exceed the
+    /// new total down to it, since no process could ever be granted more.
+    ///
+    /// Returns the processes that can no longer be guaranteed to finish,
+    /// those whose claim was cut included. It is empty unless the removal
+    /// was forced.
+    pub fn remove_capacity(
+        &mut self,
+        resource: usize,
+        units: u64,
+        force: bool,
+    ) -> Result<Vec<usize>, Denial> {
+        let available = *self
+            .available
+            .get(resource)
+            .ok_or(BankersError::UnknownResource(resource))?;
+        if units > available {
+            return Err(BankersError::CapacityInUse {
+                resource,
+                removed: units,
+                available,
+            }
+            .into());
+        }
+
+        self.resources[resource] -= units;
+        self.available[resource] -= units;
+
+        let endangered = self.check_safety().blocked_pids();
+        if endangered.is_empty() {
+            return Ok(endangered);
+        }
+        if !force {
+            self.resources[resource] += units;
+            self.available[resource] += units;
+            return Err(Denial::Endangered {
+                resource,
+                endangered,
+            });
+        }
+
+        let total = self.resources[resource];
+        let mut cut = Vec::new();
+        for process in &mut self.processes {
+            if process.max_need[resource] > total {
+                process.max_need[resource] = total;
+                process.need[resource] = total - process.allocation[resource];
+                cut.push(process.id);
+            }
+        }
+        let blocked = self.check_safety().blocked_pids();
+        Ok(self
+            .processes
+            .iter()
+            .map(|p| p.id)
+            .filter(|pid| cut.contains(pid) || blocked.contains(pid))
+            .collect())
+    }
+
+    /// Adds a new resource type with `total` units and returns its index.
+    ///
+    /// Every process starts out holding none of it, with `default_max` as
+    /// its claim. Since nothing is allocated the state stays as safe as it was.
+    pub fn add_resource_type(
+        &mut self,
+        total: u64,
+        default_max: u64,
+    ) -> Result<usize, BankersError> {
+        if self.layout_locked {
+            return Err(BankersError::ResourceTypesLocked);
+        }
+        let resource = self.resources.len();
+        if let Some(process) = self.processes.first()
+            && default_max > total
+        {
+            return Err(BankersError::MaxExceedsTotal {
+                pid: process.id,
+                resource,
+                max: default_max,
+                total,
+            });
+        }
+
+        self.resources.push(total);
+        self.available.push(total);
+        for process in &mut self.processes {
+            process.allocation.push(0);
+            process.max_need.push(default_max);
+            process.need.push(default_max);
+        }
+        for pending in &mut self.pending {
+            pending.amount.push(0);
+        }
+        Ok(resource)
+    }
+
+    /// Retires a resource type that no process holds, dropping every claim
+    /// on it. Queued requests that ask for units of it leave the queue
+    /// refused; the others no longer mention it. Later resource types shift
+    /// down by one index.
+    pub fn retire_resource_type(&mut self, resource: usize) -> Result<Retirement, BankersError> {
+        if self.layout_locked {
+            return Err(BankersError::ResourceTypesLocked);
+        }
+        if resource >= self.resources.len() {
+            return Err(BankersError::UnknownResource(resource));
+        }
+        if let Some(process) = self.processes.iter().find(|p| p.allocation[resource] > 0) {
+            return Err(BankersError::ResourceInUse {
+                resource,
+                pid: process.id,
+                allocated: process.allocation[resource],
+            });
+        }
+        if self.resources.len() == 1 {
+            return Err(BankersError::NoResources);
+        }
+
+        self.available.remove(resource);
+        for process in &mut self.processes {
+            process.allocation.remove(resource);
+            process.max_need.remove(resource);
+            process.need.remove(resource);
+        }
+        let mut refused = Vec::new();
+        self.pending.retain_mut(|pending| {
+            if pending.amount.remove(resource) == 0 {
+                return true;
+            }
+            refused.push(Wakeup {
+                ticket: pending.ticket,
+                outcome: Err(BankersError::UnknownResource(resource).into()),
+            });
+            false
+        });
+        self.record_woken(&refused);
+
+        Ok(Retirement {
+            resource,
+            units: self.resources.remove(resource),
+            refused,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{state, textbook};
+    use crate::{Queued, Scenario};
+
+    #[test]
+    fn add_capacity_frees_units() {
+        let mut banker = textbook();
+        banker.add_capacity(1, 2).unwrap();
+        assert_eq!(banker.resources(), [10, 7, 7]);
+        assert_eq!(banker.available(), [3, 5, 2]);
+
+        assert_eq!(
+            banker.add_capacity(3, 1),
+            Err(BankersError::UnknownResource(3))
+        );
+        assert_eq!(
+            banker.add_capacity(0, u64::MAX),
+            Err(BankersError::CapacityOverflow {
+                resource: 0,
+                total: 10,
+                added: u64::MAX,
+            })
+        );
+        assert_eq!(banker.resources(), [10, 7, 7]);
+    }
+
+    #[test]
+    fn remove_capacity_takes_free_units() {
+        let mut banker = textbook();
+        assert_eq!(banker.remove_capacity(0, 1, false), Ok(Vec::new()));
+        assert_eq!(banker.resources(), [9, 5, 7]);
+        assert_eq!(banker.available(), [2, 3, 2]);
+    }
+
+    #[test]
+    fn held_units_cannot_be_removed() {
+        let mut banker = textbook();
+        assert_eq!(
+            banker.remove_capacity(0, 4, true),
+            Err(Denial::Invalid(BankersError::CapacityInUse {
+                resource: 0,
+                removed: 4,
+                available: 3,
+            }))
+        );
+        assert_eq!(
+            banker.remove_capacity(5, 1, false),
+            Err(Denial::Invalid(BankersError::UnknownResource(5)))
+        );
+    }
+
+    #[test]
+    fn endangering_removal_is_refused() {
+        let mut banker = textbook();
+        let before = banker.clone();
+        assert_eq!(
+            banker.remove_capacity(0, 3, false),
+            Err(Denial::Endangered {
+                resource: 0,
+                endangered: vec![0, 2],
+            })
+        );
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn forced_removal_cuts_claims_to_the_new_total() {
+        let mut banker = textbook();
+        // P2 claims 9 of the 7 units left. Once its claim is cut to 7 everyone
+        // else can finish again, but P2 no longer gets what it declared.
+        assert_eq!(banker.remove_capacity(0, 3, true), Ok(vec![2]));
+        let process = banker.process(2).unwrap();
+        assert_eq!(process.max_need(), [7, 0, 2]);
+        assert_eq!(process.need(), [4, 0, 0]);
+        assert!(banker.is_safe_state().is_some());
+
+        // The state still passes the checks it would be loaded with.
+        let reloaded = Scenario::from(&banker).into_banker().unwrap();
+        assert_eq!(reloaded, banker);
+    }
+
+    #[test]
+    fn forced_removal_reports_processes_left_blocked() {
+        let mut banker = textbook();
+        banker.request(1, &[1, 0, 2]).unwrap();
+        // Only the claims of P0 and P4 are cut, but nobody can finish anymore.
+        assert_eq!(banker.remove_capacity(1, 3, true), Ok(vec![0, 1, 2, 3, 4]));
+        assert_eq!(banker.process(0).unwrap().max_need(), [7, 2, 3]);
+        assert_eq!(banker.process(4).unwrap().need(), [4, 2, 1]);
+        assert!(Scenario::from(&banker).into_banker().is_ok());
+    }
+
+    #[test]
+    fn new_resource_type_reaches_every_process_and_request() {
+        let mut banker = state(&[1, 1], &[(&[0, 1], &[1, 1]), (&[0, 0], &[1, 1])]);
+        assert_eq!(
+            banker.request_or_queue(1, &[1, 0], 0),
+            Ok(Queued::Waiting(0))
+        );
+        assert_eq!(
+            banker.add_resource_type(1, 2),
+            Err(BankersError::MaxExceedsTotal {
+                pid: 0,
+                resource: 2,
+                max: 2,
+                total: 1,
+            })
+        );
+
+        assert_eq!(banker.add_resource_type(4, 2), Ok(2));
+        assert_eq!(banker.resources(), [1, 1, 4]);
+        assert_eq!(banker.available(), [1, 0, 4]);
+        for process in banker.processes() {
+            assert_eq!(process.allocation()[2], 0);
+            assert_eq!(process.max_need()[2], 2);
+            assert_eq!(process.need()[2], 2);
+        }
+        assert_eq!(banker.pending()[0].amount, vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn retiring_shifts_later_types_down() {
+        let mut banker = state(
+            &[2, 3, 4],
+            &[(&[0, 1, 2], &[1, 2, 3]), (&[0, 0, 1], &[2, 1, 1])],
+        );
+        assert_eq!(
+            banker.retire_resource_type(0),
+            Ok(Retirement {
+                resource: 0,
+                units: 2,
+                refused: Vec::new(),
+            })
+        );
+        assert_eq!(banker.resources(), [3, 4]);
+        assert_eq!(banker.available(), [2, 1]);
+        let process = banker.process(0).unwrap();
+        assert_eq!(process.allocation(), [1, 2]);
+        assert_eq!(process.max_need(), [2, 3]);
+        assert_eq!(process.need(), [1, 1]);
+        let process = banker.process(1).unwrap();
+        assert_eq!(process.max_need(), [1, 1]);
+        assert_eq!(process.need(), [1, 0]);
+
+        // Adding a type and retiring it again leaves the state as it was.
+        let before = banker.clone();
+        banker.add_resource_type(5, 1).unwrap();
+        banker.retire_resource_type(2).unwrap();
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn held_or_last_resource_types_cannot_be_retired() {
+        let mut banker = textbook();
+        assert_eq!(
+            banker.retire_resource_type(1),
+            Err(BankersError::ResourceInUse {
+                resource: 1,
+                pid: 0,
+                allocated: 1,
+            })
+        );
+        assert_eq!(
+            banker.retire_resource_type(3),
+            Err(BankersError::UnknownResource(3))
+        );
+        assert_eq!(banker.resources(), [10, 5, 7]);
+
+        let mut banker = state(&[2], &[(&[0], &[1])]);
+        assert_eq!(
+            banker.retire_resource_type(0),
+            Err(BankersError::NoResources)
+        );
+        assert_eq!(banker.resources(), [2]);
+    }
+
+    #[test]
+    fn retiring_refuses_requests_for_the_retired_type() {
+        // Granting P1 the unit of R0 would be unsafe, and P0 holds R1.
+        let mut banker = state(&[1, 1], &[(&[0, 1], &[1, 1]), (&[0, 0], &[1, 1])]);
+        assert_eq!(
+            banker.request_or_queue(1, &[1, 0], 0),
+            Ok(Queued::Waiting(0))
+        );
+        assert_eq!(
+            banker.request_or_queue(1, &[0, 1], 0),
+            Ok(Queued::Waiting(1))
+        );
+
+        let retirement = banker.retire_resource_type(0).unwrap();
+        assert_eq!(retirement.units, 1);
+        assert_eq!(
+            retirement.refused,
+            vec![Wakeup {
+                ticket: 0,
+                outcome: Err(Denial::Invalid(BankersError::UnknownResource(0))),
+            }]
+        );
+        assert_eq!(banker.pending().len(), 1);
+        assert_eq!(banker.pending()[0].amount, vec![1]);
+
+        let woken = banker.finish(0).unwrap().woken;
+        assert_eq!(woken.len(), 1);
+        assert_eq!(woken[0].ticket, 1);
+        assert_eq!(woken[0].outcome.as_ref().unwrap().amount, vec![1]);
+    }
+}
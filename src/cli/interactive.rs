@@ -0,0 +1,110 @@
+use std::io;
+use std::io::Write;
+
+use bankers_algo::{BankersAlgorithm, Process};
+
+fn get_numbers_from_input() -> Option<Vec<u64>> {
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        eprintln!("Error reading input line.");
+        return None;
+    }
+
+    let numbers: Result<Vec<u64>, _> = input.split_whitespace().map(|s| s.parse::<u64>()).collect();
+
+    match numbers {
+        Ok(nums) => Some(nums),
+        Err(e) => {
+            eprintln!(
+                "Invalid number input: {}. Please enter space-separated positive integers.",
+                e
+            );
+            None
+        }
+    }
+}
+
+pub fn read_vector(prompt: &str) -> Vec<u64> {
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush().unwrap();
+
+        if let Some(values) = get_numbers_from_input() {
+            return values;
+        }
+        println!("Try again");
+    }
+}
+
+fn read_yes_no() -> bool {
+    loop {
+        print!("Create another process? [y/n]: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let trimmed_input = input.trim().to_lowercase();
+
+        match trimmed_input.as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => println!("Invalid input. Please enter 'y' or 'n'."),
+        }
+    }
+}
+
+pub fn read_system() -> Option<BankersAlgorithm> {
+    println!("--- Banker's Algorithm Initialization ---");
+
+    let resources = loop {
+        println!("Enter resources array (e.g., 10 5 7): ");
+        if let Some(res) = get_numbers_from_input()
+            && !res.is_empty()
+        {
+            break res;
+        }
+    };
+
+    let num_resources = resources.len();
+
+    let mut banker = BankersAlgorithm::from_parts(resources.clone(), Vec::new()).ok()?;
+
+    println!("\n--- Process Creation ---");
+
+    loop {
+        let process_id = banker.processes().len();
+        println!("\n --- Enter details for P{} ---", process_id);
+
+        let allocation = read_vector(&format!(
+            "Enter current allocation for P{} ({} values):",
+            process_id, num_resources
+        ));
+        let max_need = read_vector(&format!(
+            "Enter maximum need for P{} ({} values): ",
+            process_id, num_resources
+        ));
+
+        let candidate = Process::new(process_id, allocation, max_need).and_then(|process| {
+            let mut candidate = banker.processes().to_vec();
+            candidate.push(process);
+            BankersAlgorithm::from_parts(resources.clone(), candidate)
+        });
+
+        match candidate {
+            Ok(state) => banker = state,
+            Err(e) => {
+                eprintln!("Error creating process P{}: {}", process_id, e);
+                println!("Please re-enter details for P{}", process_id);
+                continue;
+            }
+        }
+
+        if !read_yes_no() {
+            break;
+        }
+    }
+
+    Some(banker)
+}
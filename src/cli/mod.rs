@@ -0,0 +1,615 @@
+mod interactive;
+mod report;
+mod script;
+mod shell;
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use bankers_algo::{
+    BankersAlgorithm, Denial, LoadError, Recovery, RecoveryCost, Scenario, Workload,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use report::{
+    Sequences, format_sequence, print_detection, print_detection_json, print_recovery,
+    print_safety, print_safety_json, print_state, print_workload,
+};
+
+/// Exit status of a run, so scripts can branch on the outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The state is safe, or the operation was carried out.
+    Safe = 0,
+    /// The state is unsafe.
+    Unsafe = 1,
+    /// The input or the operation was invalid. Also used by clap for usage errors.
+    Invalid = 2,
+    /// A request was denied.
+    Denied = 3,
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> ExitCode {
+        ExitCode::from(status as u8)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Cost {
+    /// Sacrifice as few processes as possible
+    Processes,
+    /// Sacrifice the processes holding the fewest units
+    Held,
+}
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Banker's algorithm for deadlock avoidance",
+    after_help = "Exit status: 0 safe or done, 1 unsafe, 2 invalid input, 3 request denied.",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Scenario file (.json, .toml or .txt) to load instead of prompting for the state.
+    /// A matrix text scenario piped on stdin is read as well.
+    #[arg(long, value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    /// Print every safe sequence instead of only the first one found
+    #[arg(long)]
+    all_sequences: bool,
+
+    /// Print only the number of safe sequences
+    #[arg(long, conflicts_with = "all_sequences")]
+    count_sequences: bool,
+
+    /// Stop enumerating or counting after this many safe sequences
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// Print each step of the safety algorithm as a table
+    #[arg(long)]
+    trace: bool,
+
+    /// Output format of the safety result
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check whether the state in FILE is safe
+    Check {
+        /// Scenario file, or - for a matrix scenario on stdin
+        file: PathBuf,
+
+        /// Print each step of the safety algorithm as a table
+        #[arg(long)]
+        trace: bool,
+
+        /// Output format of the safety result
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Request units for a process and grant them only if the state stays safe
+    Request(ChangeArgs),
+    /// Return units held by a process
+    Release(ChangeArgs),
+    /// Apply a script of operations such as `request P1 1 0 2`, one per line, to the state in FILE
+    Simulate {
+        /// Scenario file, or - for a matrix scenario on stdin
+        file: PathBuf,
+
+        /// Script with one operation per line, e.g. `request P1 1 0 2`
+        script: PathBuf,
+
+        /// Write the resulting state to this file
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Keep the state in FILE, or one entered at the prompts, open for interactive commands
+    Shell {
+        /// Scenario file to start from
+        file: Option<PathBuf>,
+    },
+    /// Find the processes that are deadlocked now, given each process's `request` in FILE
+    Detect {
+        /// Scenario file, or - for a matrix scenario on stdin
+        file: PathBuf,
+
+        /// Output format of the detection result
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Draw the resource-allocation graph of the state in FILE
+    Graph {
+        /// Scenario file, or - for a matrix scenario on stdin
+        file: PathBuf,
+
+        /// Graph language to write
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+
+        /// Write the graph to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Choose processes to terminate or preempt so that the state in FILE becomes safe
+    Recover {
+        /// Scenario file, or - for a matrix scenario on stdin
+        file: PathBuf,
+
+        /// Take back what the victims hold instead of terminating them
+        #[arg(long)]
+        preempt: bool,
+
+        /// What to minimize when choosing victims
+        #[arg(long, value_enum, default_value_t = Cost::Processes)]
+        cost: Cost,
+
+        /// Cost of sacrificing a process, as `P1=5`; replaces --cost.
+        /// Unlisted processes cost 0.
+        #[arg(long, value_name = "PID=COST", value_parser = parse_priority, conflicts_with = "cost")]
+        priority: Vec<(usize, u64)>,
+
+        /// Write the recovered state to this file
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Run a seeded random workload against an idle system and report how it fared
+    Workload(WorkloadArgs),
+    /// List or count the safe sequences of the state in FILE
+    Sequences {
+        /// Scenario file, or - for a matrix scenario on stdin
+        file: PathBuf,
+
+        /// Print only the number of safe sequences
+        #[arg(long)]
+        count: bool,
+
+        /// Stop after this many safe sequences
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Output format of the safety result
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+}
+
+#[derive(Args)]
+struct ChangeArgs {
+    /// Scenario file, or - for a matrix scenario on stdin
+    file: PathBuf,
+
+    /// Process id, as `1` or `P1`
+    #[arg(long, value_parser = script::parse_pid)]
+    pid: usize,
+
+    /// Units of each resource type
+    #[arg(long, num_args = 1.., required = true)]
+    amount: Vec<u64>,
+
+    /// Write the resulting state to this file
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct WorkloadArgs {
+    /// Total units of each resource type
+    #[arg(long, num_args = 1.., required = true)]
+    resources: Vec<u64>,
+
+    /// Number of processes that arrive
+    #[arg(long, default_value_t = 20)]
+    processes: usize,
+
+    /// Mean ticks between two arrivals
+    #[arg(long, value_name = "TICKS", default_value_t = 3)]
+    interarrival: u64,
+
+    /// Requests each process makes before it finishes
+    #[arg(long, default_value_t = 3)]
+    requests: usize,
+
+    /// Longest a process works between two steps
+    #[arg(long, value_name = "TICKS", default_value_t = 5)]
+    hold: u64,
+
+    /// Seed of the random number generator; the same seed repeats the run
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Rows of the utilization-over-time table
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    intervals: u64,
+}
+
+impl ReportArgs {
+    fn sequences(&self) -> Sequences {
+        if self.all_sequences {
+            Sequences::All { limit: self.limit }
+        } else if self.count_sequences {
+            Sequences::Count { limit: self.limit }
+        } else {
+            Sequences::First
+        }
+    }
+}
+
+pub fn run() -> ExitCode {
+    let cli = Cli::parse();
+
+    let status = match cli.command {
+        None => run_default(cli.input, &cli.report),
+        Some(Command::Check {
+            file,
+            trace,
+            format,
+        }) => match load(&file) {
+            Some(banker) => report(&banker, "System State", trace, Sequences::First, format),
+            None => Status::Invalid,
+        },
+        Some(Command::Sequences {
+            file,
+            count,
+            limit,
+            format,
+        }) => match load(&file) {
+            Some(banker) => {
+                let sequences = if count {
+                    Sequences::Count { limit }
+                } else {
+                    Sequences::All { limit }
+                };
+                match format {
+                    Format::Text => print_safety(&banker, false, sequences),
+                    Format::Json => print_safety_json(&banker, false, sequences),
+                }
+            }
+            None => Status::Invalid,
+        },
+        Some(Command::Detect { file, format }) => run_detect(&file, format),
+        Some(Command::Graph {
+            file,
+            format,
+            output,
+        }) => run_graph(&file, format, output.as_deref()),
+        Some(Command::Recover {
+            file,
+            preempt,
+            cost,
+            priority,
+            output,
+        }) => {
+            let recovery = if preempt {
+                Recovery::Preempt
+            } else {
+                Recovery::Terminate
+            };
+            let cost = if !priority.is_empty() {
+                RecoveryCost::Priority(priority.into_iter().collect())
+            } else {
+                match cost {
+                    Cost::Processes => RecoveryCost::FewestProcesses,
+                    Cost::Held => RecoveryCost::LeastHeld,
+                }
+            };
+            run_recover(&file, recovery, &cost, output.as_deref())
+        }
+        Some(Command::Workload(args)) => run_workload(args),
+        Some(Command::Request(args)) => run_request(args),
+        Some(Command::Release(args)) => run_release(args),
+        Some(Command::Simulate {
+            file,
+            script,
+            output,
+        }) => run_simulate(&file, &script, output.as_deref()),
+        Some(Command::Shell { file }) => {
+            let banker = match file {
+                Some(file) => load(&file),
+                None => interactive::read_system(),
+            };
+            match banker {
+                Some(banker) => {
+                    print_state(&banker, "System State");
+                    shell::run_shell(banker);
+                    Status::Safe
+                }
+                None => Status::Invalid,
+            }
+        }
+    };
+
+    status.into()
+}
+
+fn run_default(input: Option<PathBuf>, args: &ReportArgs) -> Status {
+    let banker = match input {
+        Some(path) => load(&path),
+        None if !io::stdin().is_terminal() => load(Path::new("-")),
+        None => interactive::read_system(),
+    };
+
+    match banker {
+        Some(banker) => report(
+            &banker,
+            "System State Initialized",
+            args.trace,
+            args.sequences(),
+            args.format,
+        ),
+        None => {
+            println!("Initialization failed");
+            Status::Invalid
+        }
+    }
+}
+
+fn report(
+    banker: &BankersAlgorithm,
+    title: &str,
+    trace: bool,
+    sequences: Sequences,
+    format: Format,
+) -> Status {
+    match format {
+        Format::Text => {
+            print_state(banker, title);
+            print_safety(banker, trace, sequences)
+        }
+        Format::Json => print_safety_json(banker, trace, sequences),
+    }
+}
+
+fn run_detect(file: &Path, format: Format) -> Status {
+    let Some(scenario) = load_scenario(file) else {
+        return Status::Invalid;
+    };
+    let requests = scenario.requests();
+    let detection =
+        into_banker(file, scenario).and_then(|banker| match banker.detect_deadlock(&requests) {
+            Ok(report) => Some((banker, report)),
+            Err(e) => {
+                eprintln!("Error! {}", e);
+                None
+            }
+        });
+
+    match (detection, format) {
+        (Some((banker, report)), Format::Text) => {
+            print_state(&banker, "System State");
+            print_detection(&report)
+        }
+        (Some((_, report)), Format::Json) => print_detection_json(&report),
+        (None, _) => Status::Invalid,
+    }
+}
+
+fn run_graph(file: &Path, format: GraphFormat, output: Option<&Path>) -> Status {
+    let Some(banker) = load(file) else {
+        return Status::Invalid;
+    };
+    let graph = match format {
+        GraphFormat::Dot => banker.to_dot(),
+        GraphFormat::Mermaid => banker.to_mermaid(),
+    };
+
+    match output {
+        None => {
+            print!("{}", graph);
+            Status::Safe
+        }
+        Some(path) => match fs::write(path, graph) {
+            Ok(()) => Status::Safe,
+            Err(e) => {
+                eprintln!("Error! {}: {}", path.display(), e);
+                Status::Invalid
+            }
+        },
+    }
+}
+
+fn run_recover(
+    file: &Path,
+    recovery: Recovery,
+    cost: &RecoveryCost,
+    output: Option<&Path>,
+) -> Status {
+    let Some(mut banker) = load(file) else {
+        return Status::Invalid;
+    };
+
+    print_state(&banker, "Initial State");
+    let plan = banker.recover(recovery, cost);
+    print_recovery(&plan);
+    print_state(&banker, "Recovered State");
+    save(&banker, output)
+}
+
+fn parse_priority(arg: &str) -> Result<(usize, u64), String> {
+    let (pid, cost) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("Expected PID=COST, got '{}'.", arg))?;
+    let cost = cost
+        .parse()
+        .map_err(|_| format!("Invalid cost '{}'.", cost))?;
+    Ok((script::parse_pid(pid)?, cost))
+}
+
+fn run_workload(args: WorkloadArgs) -> Status {
+    let workload = Workload {
+        resources: args.resources,
+        processes: args.processes,
+        interarrival: args.interarrival,
+        requests_per_process: args.requests,
+        max_hold: args.hold,
+        seed: args.seed,
+    };
+
+    match workload.run() {
+        Ok(report) => {
+            print_workload(&report, args.intervals);
+            Status::Safe
+        }
+        Err(e) => {
+            eprintln!("Error! {}", e);
+            Status::Invalid
+        }
+    }
+}
+
+fn run_request(args: ChangeArgs) -> Status {
+    let Some(mut banker) = load(&args.file) else {
+        return Status::Invalid;
+    };
+
+    match banker.request(args.pid, &args.amount) {
+        Ok(grant) => {
+            println!("Request granted: P{} {:?}", grant.pid, grant.amount);
+            println!("  Available: {:?}", banker.available());
+            println!("  Safe sequence: {}", format_sequence(&grant.safe_sequence));
+            save(&banker, args.output.as_deref())
+        }
+        Err(Denial::Invalid(e)) => {
+            eprintln!("Error! {}", e);
+            Status::Invalid
+        }
+        Err(denial) => {
+            eprintln!("Request denied: {}", denial);
+            Status::Denied
+        }
+    }
+}
+
+fn run_release(args: ChangeArgs) -> Status {
+    let Some(mut banker) = load(&args.file) else {
+        return Status::Invalid;
+    };
+
+    match banker.release(args.pid, &args.amount) {
+        Ok(release) => {
+            println!("Released: P{} {:?}", release.pid, release.amount);
+            println!("  Available: {:?}", banker.available());
+            save(&banker, args.output.as_deref())
+        }
+        Err(e) => {
+            eprintln!("Error! {}", e);
+            Status::Invalid
+        }
+    }
+}
+
+fn run_simulate(file: &Path, script: &Path, output: Option<&Path>) -> Status {
+    let Some(mut banker) = load(file) else {
+        return Status::Invalid;
+    };
+    let script = match fs::read_to_string(script) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("Error! {}: {}", script.display(), e);
+            return Status::Invalid;
+        }
+    };
+
+    print_state(&banker, "Initial State");
+    println!();
+    let status = script::run_script(&mut banker, &script);
+    if status == Status::Invalid {
+        return status;
+    }
+
+    print_state(&banker, "Final State");
+    let safety = print_safety(&banker, false, Sequences::First);
+    if save(&banker, output) == Status::Invalid {
+        return Status::Invalid;
+    }
+    if safety == Status::Unsafe {
+        safety
+    } else {
+        status
+    }
+}
+
+/// Loads a scenario file, or a matrix scenario from stdin for `-`.
+fn load(path: &Path) -> Option<BankersAlgorithm> {
+    into_banker(path, load_scenario(path)?)
+}
+
+fn load_scenario(path: &Path) -> Option<Scenario> {
+    let result = if path == Path::new("-") {
+        read_piped()
+    } else {
+        Scenario::load(path)
+    };
+
+    match result {
+        Ok(scenario) => Some(scenario),
+        Err(e) => {
+            eprintln!("Error! {}: {}", source_name(path), e);
+            None
+        }
+    }
+}
+
+fn into_banker(path: &Path, scenario: Scenario) -> Option<BankersAlgorithm> {
+    match scenario.into_banker() {
+        Ok(banker) => Some(banker),
+        Err(e) => {
+            eprintln!("Error! {}: {}", source_name(path), e);
+            None
+        }
+    }
+}
+
+fn source_name(path: &Path) -> String {
+    if path == Path::new("-") {
+        "stdin".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+fn read_piped() -> Result<Scenario, LoadError> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    Scenario::from_matrix(&input)
+}
+
+fn save(banker: &BankersAlgorithm, output: Option<&Path>) -> Status {
+    let Some(path) = output else {
+        return Status::Safe;
+    };
+    match banker.save(path) {
+        Ok(()) => Status::Safe,
+        Err(e) => {
+            eprintln!("Error! {}: {}", path.display(), e);
+            Status::Invalid
+        }
+    }
+}
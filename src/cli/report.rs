@@ -0,0 +1,346 @@
+use bankers_algo::{
+    BankersAlgorithm, Recovery, RecoveryPlan, SafetyReport, TraceStep, WorkloadReport,
+};
+use serde::Serialize;
+
+use super::Status;
+
+/// Which safe sequences to print for a safe state.
+#[derive(Clone, Copy)]
+pub enum Sequences {
+    First,
+    All { limit: Option<usize> },
+    Count { limit: Option<usize> },
+}
+
+pub fn print_state(banker: &BankersAlgorithm, title: &str) {
+    println!("\n--- {} ---", title);
+    println!("Total Resources: {:?}", banker.resources());
+    println!("Available: {:?}", banker.available());
+
+    for p in banker.processes() {
+        println!(
+            " P{}: Allocated={:?}, Max={:?}, Need={:?} ",
+            p.id(),
+            p.allocation(),
+            p.max_need(),
+            p.need()
+        );
+    }
+    for pending in banker.pending() {
+        println!(
+            " Waiting #{}: P{} requests {:?}",
+            pending.ticket, pending.pid, pending.amount
+        );
+    }
+    println!("-----------------------------------");
+}
+
+pub fn format_sequence(sequence: &[usize]) -> String {
+    let seq: Vec<String> = sequence.iter().map(|&id| format!("P{}", id)).collect();
+    seq.join(" -> ")
+}
+
+pub fn format_pids(pids: &[usize]) -> String {
+    let pids: Vec<String> = pids.iter().map(|&id| format!("P{}", id)).collect();
+    pids.join(", ")
+}
+
+/// Runs the safety check and prints the verdict, the requested sequences and,
+/// for an unsafe state, the diagnosis.
+pub fn print_safety(banker: &BankersAlgorithm, trace: bool, sequences: Sequences) -> Status {
+    println!("\n--- Checking System Safety ---");
+
+    let report = if trace {
+        let (report, trace) = banker.trace_safety();
+        print_trace(&trace);
+        report
+    } else {
+        banker.check_safety()
+    };
+
+    match report.safe_sequence() {
+        Some(sequence) => {
+            println!("System is in a safe state.");
+
+            match sequences {
+                Sequences::First => {
+                    println!("  Safe sequence: {}", format_sequence(sequence));
+                }
+                Sequences::All { limit } => {
+                    let sequences = banker.all_safe_sequences(limit);
+                    println!("  Safe sequences:");
+                    for (n, sequence) in sequences.iter().enumerate() {
+                        println!("  {:>4}. {}", n + 1, format_sequence(sequence));
+                    }
+                    if limit == Some(sequences.len()) {
+                        println!("  (stopped after {} sequences)", sequences.len());
+                    }
+                }
+                Sequences::Count { limit } => {
+                    let limit = limit.map(|limit| limit as u128);
+                    let count = banker.count_safe_sequences(limit);
+                    if limit == Some(count) {
+                        println!("  Safe sequences: at least {}", count);
+                    } else {
+                        println!("  Safe sequences: {}", count);
+                    }
+                }
+            }
+            Status::Safe
+        }
+        None => {
+            eprintln!("System is in an unsafe state! Deadlock potential exists");
+            print_diagnosis(&report);
+            Status::Unsafe
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SafetyOutput<'a> {
+    safe: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence: Option<&'a [usize]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequences: Option<Vec<Vec<usize>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence_count: Option<u128>,
+    available: &'a [u64],
+    processes: Vec<ProcessOutput<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diagnosis: Option<&'a SafetyReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<&'a [TraceStep]>,
+}
+
+#[derive(Serialize)]
+struct ProcessOutput<'a> {
+    id: usize,
+    allocation: &'a [u64],
+    max: &'a [u64],
+    need: &'a [u64],
+}
+
+/// Same checks as [`print_safety`], printed as a single JSON document.
+pub fn print_safety_json(banker: &BankersAlgorithm, trace: bool, sequences: Sequences) -> Status {
+    let (report, trace) = if trace {
+        let (report, trace) = banker.trace_safety();
+        (report, Some(trace))
+    } else {
+        (banker.check_safety(), None)
+    };
+    let safe = report.is_safe();
+
+    let mut output = SafetyOutput {
+        safe,
+        sequence: report.safe_sequence(),
+        sequences: None,
+        sequence_count: None,
+        available: banker.available(),
+        processes: banker
+            .processes()
+            .iter()
+            .map(|p| ProcessOutput {
+                id: p.id(),
+                allocation: p.allocation(),
+                max: p.max_need(),
+                need: p.need(),
+            })
+            .collect(),
+        diagnosis: (!safe).then_some(&report),
+        trace: trace.as_deref(),
+    };
+    if safe {
+        match sequences {
+            Sequences::First => {}
+            Sequences::All { limit } => output.sequences = Some(banker.all_safe_sequences(limit)),
+            Sequences::Count { limit } => {
+                output.sequence_count =
+                    Some(banker.count_safe_sequences(limit.map(|limit| limit as u128)))
+            }
+        }
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string(&output).expect("safety output serializes to JSON")
+    );
+    if safe { Status::Safe } else { Status::Unsafe }
+}
+
+/// Prints which processes are deadlocked and what each one is waiting for.
+pub fn print_detection(report: &SafetyReport) -> Status {
+    println!("\n--- Detecting Deadlock ---");
+    if report.is_safe() {
+        println!(
+            "No deadlock. Every process can finish in the order {}.",
+            format_sequence(&report.finished)
+        );
+        return Status::Safe;
+    }
+
+    eprintln!(
+        "Deadlock detected! Deadlocked: {}",
+        format_pids(&report.blocked_pids())
+    );
+    print_diagnosis(report);
+    Status::Unsafe
+}
+
+#[derive(Serialize)]
+struct DetectionOutput<'a> {
+    deadlock: bool,
+    deadlocked: Vec<usize>,
+    diagnosis: &'a SafetyReport,
+}
+
+/// Same as [`print_detection`], printed as a single JSON document.
+pub fn print_detection_json(report: &SafetyReport) -> Status {
+    let output = DetectionOutput {
+        deadlock: !report.is_safe(),
+        deadlocked: report.blocked_pids(),
+        diagnosis: report,
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&output).expect("detection output serializes to JSON")
+    );
+    if output.deadlock {
+        Status::Unsafe
+    } else {
+        Status::Safe
+    }
+}
+
+pub fn print_recovery(plan: &RecoveryPlan) {
+    println!("\n--- Recovery Plan ---");
+    if plan.victims.is_empty() {
+        println!("System is already in a safe state. Nothing to do.");
+    } else {
+        let action = match plan.recovery {
+            Recovery::Terminate => "Terminate",
+            Recovery::Preempt => "Preempt",
+        };
+        println!(
+            "{}: {} (cost {})",
+            action,
+            format_pids(&plan.victims),
+            plan.cost
+        );
+        println!("  Reclaimed: {:?}", plan.reclaimed);
+    }
+    println!("  Safe sequence: {}", format_sequence(&plan.safe_sequence));
+}
+
+/// Prints the workload metrics and utilization averaged over `intervals`
+/// equal stretches of the run.
+pub fn print_workload(report: &WorkloadReport, intervals: u64) {
+    let percentages = |shares: Vec<f64>| -> Vec<String> {
+        shares
+            .iter()
+            .map(|share| format!("{:.1}%", share * 100.0))
+            .collect()
+    };
+
+    println!("\n--- Workload Simulation ---");
+    println!(
+        "Completed: {} processes in {} ticks",
+        report.completed, report.duration
+    );
+    println!("Throughput: {:.3} processes per tick", report.throughput());
+    println!(
+        "Average wait: {:.2} ticks per granted request",
+        report.average_wait()
+    );
+    println!(
+        "Denial rate: {:.1}% ({} of {} attempts)",
+        report.denial_rate() * 100.0,
+        report.denials,
+        report.attempts
+    );
+    let average: Vec<String> = percentages(report.average_utilization())
+        .iter()
+        .enumerate()
+        .map(|(k, share)| format!("R{} {}", k, share))
+        .collect();
+    println!("Average utilization: {}", average.join(", "));
+
+    let intervals = intervals.clamp(1, report.duration.max(1));
+    let width = report.duration.div_ceil(intervals).max(1);
+    println!("\nUtilization over time:");
+    let header: Vec<String> = (0..average.len())
+        .map(|k| format!("{:>6}", format!("R{}", k)))
+        .collect();
+    println!("  {:^13} {}", "Ticks", header.join(" "));
+    let mut start = 0;
+    while start < report.duration {
+        let end = start.saturating_add(width).min(report.duration);
+        println!(
+            "  {:>6}-{:<6} {}",
+            start,
+            end,
+            percentages(report.utilization_between(start, end))
+                .iter()
+                .map(|share| format!("{:>6}", share))
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+        start = end;
+    }
+}
+
+fn print_trace(trace: &[TraceStep]) {
+    let rows: Vec<[String; 6]> = trace
+        .iter()
+        .map(|step| {
+            [
+                step.pass.to_string(),
+                format!("P{}", step.pid),
+                format!("{:?}", step.need),
+                format!("{:?}", step.work),
+                if step.chosen { "yes" } else { "no" }.to_string(),
+                format!("{:?}", step.work_after),
+            ]
+        })
+        .collect();
+    let header = ["Pass", "Process", "Need", "Work", "Chosen", "Work after"].map(String::from);
+
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell))
+            .collect();
+        println!("  {}", cells.join(" | ").trim_end());
+    }
+}
+
+fn print_diagnosis(report: &SafetyReport) {
+    if report.finished.is_empty() {
+        println!("  No process can finish.");
+    } else {
+        println!("  Can finish: {}", format_sequence(&report.finished));
+    }
+    println!("  Available after that: {:?}", report.work);
+
+    for blocked in &report.blocked {
+        println!("  P{} cannot finish:", blocked.pid);
+        for shortfall in &blocked.shortfalls {
+            println!(
+                "    resource {}: needs {}, only {} available (short by {})",
+                shortfall.resource,
+                shortfall.need,
+                shortfall.work,
+                shortfall.missing()
+            );
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::str::FromStr;
+
+use bankers_algo::{BankersAlgorithm, Denial, Queued, Wakeup};
+
+use super::Status;
+use super::report::{format_pids, format_sequence};
+
+/// One line of a simulation script, e.g. `request P1 1 0 2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Request {
+        pid: usize,
+        amount: Vec<u64>,
+    },
+    Release {
+        pid: usize,
+        amount: Vec<u64>,
+    },
+    Wait {
+        pid: usize,
+        amount: Vec<u64>,
+    },
+    Finish {
+        pid: usize,
+    },
+    AddCapacity {
+        resource: usize,
+        units: u64,
+    },
+    RemoveCapacity {
+        resource: usize,
+        units: u64,
+        force: bool,
+    },
+    AddResource {
+        total: u64,
+        default_max: u64,
+    },
+    RetireResource {
+        resource: usize,
+    },
+    Admit {
+        max_need: Vec<u64>,
+    },
+    Claim {
+        pid: usize,
+        additional: Vec<u64>,
+    },
+    Revise {
+        pid: usize,
+        max_need: Vec<u64>,
+    },
+}
+
+/// Accepts both `P1` and `1`.
+pub fn parse_pid(token: &str) -> Result<usize, String> {
+    token
+        .strip_prefix(['P', 'p'])
+        .unwrap_or(token)
+        .parse()
+        .map_err(|_| format!("Invalid process id '{}'.", token))
+}
+
+/// Accepts both `R1` and `1`.
+pub fn parse_resource(token: &str) -> Result<usize, String> {
+    token
+        .strip_prefix(['R', 'r'])
+        .unwrap_or(token)
+        .parse()
+        .map_err(|_| format!("Invalid resource index '{}'.", token))
+}
+
+fn parse_units(token: &str) -> Result<u64, String> {
+    token
+        .parse()
+        .map_err(|_| format!("Invalid number '{}'.", token))
+}
+
+pub fn parse_amount(tokens: &[&str]) -> Result<Vec<u64>, String> {
+    if tokens.is_empty() {
+        return Err("Expected unit counts after the process id.".to_string());
+    }
+    tokens.iter().map(|token| parse_units(token)).collect()
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Operation, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["request", pid, amount @ ..] => Ok(Operation::Request {
+                pid: parse_pid(pid)?,
+                amount: parse_amount(amount)?,
+            }),
+            ["release", pid, amount @ ..] => Ok(Operation::Release {
+                pid: parse_pid(pid)?,
+                amount: parse_amount(amount)?,
+            }),
+            ["wait", pid, amount @ ..] => Ok(Operation::Wait {
+                pid: parse_pid(pid)?,
+                amount: parse_amount(amount)?,
+            }),
+            ["finish", pid] => Ok(Operation::Finish {
+                pid: parse_pid(pid)?,
+            }),
+            ["add-capacity", resource, units] => Ok(Operation::AddCapacity {
+                resource: parse_resource(resource)?,
+                units: parse_units(units)?,
+            }),
+            ["remove-capacity", resource, units, force @ ..] if force.len() <= 1 => {
+                Ok(Operation::RemoveCapacity {
+                    resource: parse_resource(resource)?,
+                    units: parse_units(units)?,
+                    force: match force {
+                        [] => false,
+                        ["force"] => true,
+                        _ => return Err(format!("Expected 'force', got '{}'.", force[0])),
+                    },
+                })
+            }
+            ["add-resource", total] => Ok(Operation::AddResource {
+                total: parse_units(total)?,
+                default_max: 0,
+            }),
+            ["add-resource", total, default_max] => Ok(Operation::AddResource {
+                total: parse_units(total)?,
+                default_max: parse_units(default_max)?,
+            }),
+            ["retire-resource", resource] => Ok(Operation::RetireResource {
+                resource: parse_resource(resource)?,
+            }),
+            ["admit", max_need @ ..] if !max_need.is_empty() => Ok(Operation::Admit {
+                max_need: parse_amount(max_need)?,
+            }),
+            ["claim", pid, additional @ ..] => Ok(Operation::Claim {
+                pid: parse_pid(pid)?,
+                additional: parse_amount(additional)?,
+            }),
+            ["revise", pid, max_need @ ..] => Ok(Operation::Revise {
+                pid: parse_pid(pid)?,
+                max_need: parse_amount(max_need)?,
+            }),
+            _ => Err(format!(
+                "Unknown operation '{}'. Expected request, wait, release, finish, admit, claim, revise, \
+                 add-capacity, remove-capacity, add-resource or retire-resource.",
+                line.trim()
+            )),
+        }
+    }
+}
+
+/// Applies one operation, describing what changed.
+///
+/// Invalid operations come back as [`Denial::Invalid`].
+pub fn apply(banker: &mut BankersAlgorithm, operation: Operation) -> Result<String, Denial> {
+    match operation {
+        Operation::Request { pid, amount } => banker.request(pid, &amount).map(|grant| {
+            format!(
+                "granted (safe sequence: {})",
+                format_sequence(&grant.safe_sequence)
+            )
+        }),
+        Operation::Wait { pid, amount } => match banker.request_or_queue(pid, &amount, 0)? {
+            Queued::Granted(grant) => Ok(format!(
+                "granted (safe sequence: {})",
+                format_sequence(&grant.safe_sequence)
+            )),
+            Queued::Waiting(ticket) => Ok(format!("queued as #{}", ticket)),
+        },
+        Operation::Release { pid, amount } => {
+            let release = banker.release(pid, &amount)?;
+            Ok(format!(
+                "released{}, available {:?}",
+                format_wakeups(&release.woken),
+                banker.available()
+            ))
+        }
+        Operation::Finish { pid } => {
+            let release = banker.finish(pid)?;
+            Ok(format!(
+                "finished, released {:?}{}",
+                release.amount,
+                format_wakeups(&release.woken)
+            ))
+        }
+        Operation::AddCapacity { resource, units } => {
+            banker.add_capacity(resource, units)?;
+            let woken = format_wakeups(&banker.retry_pending());
+            Ok(format!(
+                "added, resources {:?}{}",
+                banker.resources(),
+                woken
+            ))
+        }
+        Operation::RemoveCapacity {
+            resource,
+            units,
+            force,
+        } => {
+            let endangered = banker.remove_capacity(resource, units, force)?;
+            let woken = format_wakeups(&banker.retry_pending());
+            if endangered.is_empty() {
+                Ok(format!(
+                    "removed, resources {:?}{}",
+                    banker.resources(),
+                    woken
+                ))
+            } else {
+                Ok(format!(
+                    "removed by force, endangering {}{}",
+                    format_pids(&endangered),
+                    woken
+                ))
+            }
+        }
+        Operation::AddResource { total, default_max } => {
+            let resource = banker.add_resource_type(total, default_max)?;
+            Ok(format!(
+                "added R{}, resources {:?}",
+                resource,
+                banker.resources()
+            ))
+        }
+        Operation::RetireResource { resource } => {
+            let mut woken = banker.retire_resource_type(resource)?.refused;
+            woken.extend(banker.retry_pending());
+            let woken = format_wakeups(&woken);
+            Ok(format!(
+                "retired, resources {:?}{}",
+                banker.resources(),
+                woken
+            ))
+        }
+        Operation::Admit { max_need } => {
+            let pid = banker.admit(&max_need)?;
+            Ok(format!("admitted as P{}", pid))
+        }
+        Operation::Claim { pid, additional } => {
+            banker.declare_claim(pid, &additional)?;
+            let process = banker.process(pid).expect("claimed process exists");
+            Ok(format!("claimed, max {:?}", process.max_need()))
+        }
+        Operation::Revise { pid, max_need } => {
+            banker.revise_max(pid, &max_need)?;
+            let woken = format_wakeups(&banker.retry_pending());
+            let process = banker.process(pid).expect("revised process exists");
+            Ok(format!("revised, need {:?}{}", process.need(), woken))
+        }
+    }
+}
+
+/// Describes the queued requests that a change settled, if any.
+fn format_wakeups(woken: &[Wakeup]) -> String {
+    let woken: Vec<String> = woken
+        .iter()
+        .map(|wakeup| match &wakeup.outcome {
+            Ok(grant) => format!("#{} P{} granted", wakeup.ticket, grant.pid),
+            Err(denial) => format!("#{} refused: {}", wakeup.ticket, denial),
+        })
+        .collect();
+    if woken.is_empty() {
+        String::new()
+    } else {
+        format!(", woke {}", woken.join(", "))
+    }
+}
+
+/// Applies every operation in `script` in order, reporting each outcome.
+///
+/// Denied requests are skipped; an invalid line or operation stops the run.
+pub fn run_script(banker: &mut BankersAlgorithm, script: &str) -> Status {
+    let mut status = Status::Safe;
+
+    for (index, line) in script.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let operation = match line.parse::<Operation>() {
+            Ok(operation) => operation,
+            Err(e) => {
+                eprintln!("Error! line {}: {}", line_number, e);
+                return Status::Invalid;
+            }
+        };
+
+        match apply(banker, operation) {
+            Ok(message) => println!("{:>4}: {:<24} {}", line_number, line, message),
+            Err(Denial::Invalid(e)) => {
+                eprintln!("Error! line {}: {}", line_number, e);
+                return Status::Invalid;
+            }
+            Err(denial) => {
+                status = Status::Denied;
+                println!("{:>4}: {:<24} denied: {}", line_number, line, denial);
+            }
+        }
+    }
+
+    status
+}
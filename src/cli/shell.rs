@@ -0,0 +1,165 @@
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use bankers_algo::{BankersAlgorithm, BankersError};
+
+use super::interactive::read_vector;
+use super::report::{Sequences, format_pids, format_sequence, print_safety, print_state};
+use super::script::{self, Operation};
+
+const HELP: &str = "\
+Commands:
+  request P<id> <units...>   request units, granted only if the state stays safe
+  wait P<id> <units...>      request units, queueing them until granting is safe
+  release P<id> <units...>   return units held by a process, waking queued requests
+  add-process                enter a new process's allocation and maximum
+  admit <units...>           admit a new process with this maximum claim, if safe
+  claim P<id> <units...>     raise a process's maximum claim, if safe
+  revise P<id> <units...>    replace a process's maximum claim
+  remove P<id>               terminate a process, releasing what it holds
+  add-capacity R<k> <units>  add units of a resource type
+  remove-capacity R<k> <units> [force]
+                             remove free units, refused if that endangers a process
+  add-resource <units> [max] add a resource type, claiming `max` for every process
+  retire-resource R<k>       drop a resource type no process holds
+  show                       print the current state
+  safe?                      run the safety check
+  undo                       revert the last change
+  save <file>                write the state as .json, .toml or .txt
+  help                       print this message
+  quit                       leave the shell";
+
+/// Keeps `banker` alive across commands read from stdin until `quit` or EOF.
+pub fn run_shell(mut banker: BankersAlgorithm) {
+    let mut history: Vec<BankersAlgorithm> = Vec::new();
+
+    println!("\nType 'help' for a list of commands.");
+    loop {
+        print!("banker> ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Error reading input line: {}", e);
+                break;
+            }
+        }
+
+        let line = input.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let before = banker.clone();
+
+        let changed = match command {
+            "" => false,
+            "help" => {
+                println!("{}", HELP);
+                false
+            }
+            "quit" | "exit" => break,
+            "show" => {
+                print_state(&banker, "System State");
+                false
+            }
+            "safe?" => {
+                print_safety(&banker, false, Sequences::First);
+                false
+            }
+            "undo" => {
+                match history.pop() {
+                    Some(previous) => {
+                        banker = previous;
+                        println!("Reverted the last change.");
+                        print_verdict(&banker);
+                    }
+                    None => println!("Nothing to undo."),
+                }
+                false
+            }
+            "save" => {
+                let path = argument.trim();
+                if path.is_empty() {
+                    eprintln!("Error! Usage: save <file>");
+                } else if let Err(e) = banker.save(Path::new(path)) {
+                    eprintln!("Error! {}: {}", path, e);
+                } else {
+                    println!("Saved to {}.", path);
+                }
+                false
+            }
+            "add-process" => add_process(&mut banker),
+            "remove" => apply(&mut banker, &format!("finish {}", argument)),
+            _ => apply(&mut banker, line),
+        };
+
+        if changed {
+            history.push(before);
+            print_verdict(&banker);
+        }
+    }
+}
+
+fn apply(banker: &mut BankersAlgorithm, line: &str) -> bool {
+    let operation = match line.parse::<Operation>() {
+        Ok(operation) => operation,
+        Err(e) => {
+            eprintln!("Error! {} Type 'help' for a list of commands.", e);
+            return false;
+        }
+    };
+
+    match script::apply(banker, operation) {
+        Ok(message) => {
+            println!("{}", message);
+            true
+        }
+        Err(denial) => {
+            eprintln!("Denied: {}", denial);
+            false
+        }
+    }
+}
+
+fn add_process(banker: &mut BankersAlgorithm) -> bool {
+    let Some(pid) = banker.next_pid() else {
+        eprintln!("Error! {}", BankersError::ProcessIdsExhausted);
+        return false;
+    };
+    let num_resources = banker.resources().len();
+
+    let allocation = read_vector(&format!(
+        "Enter current allocation for P{} ({} values): ",
+        pid, num_resources
+    ));
+    let max_need = read_vector(&format!(
+        "Enter maximum need for P{} ({} values): ",
+        pid, num_resources
+    ));
+
+    match banker.add_process(&allocation, &max_need) {
+        Ok(pid) => {
+            println!("Added P{}.", pid);
+            true
+        }
+        Err(e) => {
+            eprintln!("Error creating process P{}: {}", pid, e);
+            false
+        }
+    }
+}
+
+fn print_verdict(banker: &BankersAlgorithm) {
+    let report = banker.check_safety();
+    match report.safe_sequence() {
+        Some(sequence) => println!("State is safe: {}", format_sequence(sequence)),
+        None => {
+            println!(
+                "State is UNSAFE. Cannot finish: {}. Use 'undo' to revert.",
+                format_pids(&report.blocked_pids())
+            );
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{BankersAlgorithm, BankersError, SafetyReport};
+
+impl BankersAlgorithm {
+    /// Finds the processes that are deadlocked right now, given what each
+    /// one is currently waiting for.
+    ///
+    /// `requests` holds one row per process, in the order of
+    /// [`processes`](Self::processes). Unlike the safety check this ignores
+    /// the declared maxima and assumes a process finishes once its current
+    /// request is met. A process that holds nothing cannot be part of a
+    /// deadlock. The deadlocked processes are the report's `blocked` ones.
+    pub fn detect_deadlock(&self, requests: &[Vec<u64>]) -> Result<SafetyReport, BankersError> {
+        if requests.len() != self.processes.len() {
+            return Err(BankersError::RequestCountMismatch {
+                expected: self.processes.len(),
+                found: requests.len(),
+            });
+        }
+
+        let nothing = vec![0; self.resources.len()];
+        let mut demands: Vec<&[u64]> = Vec::with_capacity(requests.len());
+        for (process, request) in self.processes.iter().zip(requests) {
+            self.check_len(process.id, request)?;
+            if process.allocation.iter().all(|&units| units == 0) {
+                demands.push(&nothing);
+            } else {
+                demands.push(request);
+            }
+        }
+
+        Ok(self.run_safety(&demands, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::state;
+
+    /// P0 and P1 each hold one of two single-unit resources. P2 holds nothing.
+    fn crossed() -> BankersAlgorithm {
+        state(
+            &[1, 1],
+            &[(&[1, 0], &[1, 1]), (&[0, 1], &[1, 1]), (&[0, 0], &[1, 1])],
+        )
+    }
+
+    #[test]
+    fn finds_a_cycle() {
+        let report = crossed()
+            .detect_deadlock(&[vec![0, 1], vec![1, 0], vec![0, 0]])
+            .unwrap();
+        assert!(!report.is_safe());
+        assert_eq!(report.blocked_pids(), vec![0, 1]);
+        assert_eq!(report.finished, vec![2]);
+    }
+
+    #[test]
+    fn clears_a_state_without_deadlock() {
+        let report = crossed()
+            .detect_deadlock(&[vec![0, 1], vec![0, 0], vec![0, 0]])
+            .unwrap();
+        assert!(report.is_safe());
+        assert_eq!(report.finished, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn process_holding_nothing_is_never_deadlocked() {
+        let report = crossed()
+            .detect_deadlock(&[vec![0, 0], vec![0, 0], vec![5, 5]])
+            .unwrap();
+        assert!(report.is_safe());
+        assert!(report.finished.contains(&2));
+    }
+
+    #[test]
+    fn needs_one_request_per_process() {
+        let banker = crossed();
+        assert_eq!(
+            banker.detect_deadlock(&[vec![0, 1], vec![1, 0]]),
+            Err(BankersError::RequestCountMismatch {
+                expected: 3,
+                found: 2,
+            })
+        );
+        assert_eq!(
+            banker.detect_deadlock(&[vec![0, 1], vec![1], vec![0, 0]]),
+            Err(BankersError::ResourceCountMismatch {
+                pid: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fmt;
+
+/// Reasons a system state, or an operation on it, is rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BankersError {
+    NoResources,
+    UnknownProcess(usize),
+    UnknownResource(usize),
+    DuplicateProcess(usize),
+    ResourceCountMismatch {
+        pid: usize,
+        expected: usize,
+        found: usize,
+    },
+    LengthMismatch {
+        pid: usize,
+        allocation: usize,
+        max_need: usize,
+    },
+    AllocationExceedsMax {
+        pid: usize,
+        resource: usize,
+        allocation: u64,
+        max: u64,
+    },
+    MaxExceedsTotal {
+        pid: usize,
+        resource: usize,
+        max: u64,
+        total: u64,
+    },
+    OverAllocated {
+        pid: usize,
+        resource: usize,
+        allocated: u64,
+        total: u64,
+    },
+    ReleaseExceedsAllocation {
+        pid: usize,
+        resource: usize,
+        released: u64,
+        allocated: u64,
+    },
+    CapacityInUse {
+        resource: usize,
+        removed: u64,
+        available: u64,
+    },
+    CapacityOverflow {
+        resource: usize,
+        total: u64,
+        added: u64,
+    },
+    ResourceInUse {
+        resource: usize,
+        pid: usize,
+        allocated: u64,
+    },
+    RequestCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    ProcessIdsExhausted,
+    ResourceTypesLocked,
+}
+
+impl fmt::Display for BankersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BankersError::NoResources => write!(f, "At least one resource type is required."),
+            BankersError::UnknownProcess(pid) => write!(f, "Process {}: No such process.", pid),
+            BankersError::UnknownResource(resource) => {
+                write!(f, "Resource {}: No such resource type.", resource)
+            }
+            BankersError::DuplicateProcess(pid) => {
+                write!(f, "Process {}: Duplicate process id.", pid)
+            }
+            BankersError::ResourceCountMismatch {
+                pid,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Process {}: Expected {} resource values, got {}.",
+                pid, expected, found
+            ),
+            BankersError::LengthMismatch {
+                pid,
+                allocation,
+                max_need,
+            } => write!(
+                f,
+                "Process {}: Allocation ({} values) and Max Need ({} values) length mismatch.",
+                pid, allocation, max_need
+            ),
+            BankersError::AllocationExceedsMax {
+                pid,
+                resource,
+                allocation,
+                max,
+            } => write!(
+                f,
+                "Process {}: Allocation ({}) exceeds Max Need ({}) for resource {}.",
+                pid, allocation, max, resource
+            ),
+            BankersError::MaxExceedsTotal {
+                pid,
+                resource,
+                max,
+                total,
+            } => write!(
+                f,
+                "Process {}: Max Need ({}) exceeds total system resources ({}) for resource {}.",
+                pid, max, total, resource
+            ),
+            BankersError::OverAllocated {
+                pid,
+                resource,
+                allocated,
+                total,
+            } => write!(
+                f,
+                "Process {}: Total allocated resources ({}) for resource {} exceed total system resources ({}).",
+                pid, allocated, resource, total
+            ),
+            BankersError::ReleaseExceedsAllocation {
+                pid,
+                resource,
+                released,
+                allocated,
+            } => write!(
+                f,
+                "Process {}: Release ({}) exceeds current allocation ({}) for resource {}.",
+                pid, released, allocated, resource
+            ),
+            BankersError::CapacityInUse {
+                resource,
+                removed,
+                available,
+            } => write!(
+                f,
+                "Resource {}: Cannot remove {} units, only {} are not held by a process.",
+                resource, removed, available
+            ),
+            BankersError::CapacityOverflow {
+                resource,
+                total,
+                added,
+            } => write!(
+                f,
+                "Resource {}: Adding {} units to {} overflows the unit count.",
+                resource, added, total
+            ),
+            BankersError::ResourceInUse {
+                resource,
+                pid,
+                allocated,
+            } => write!(
+                f,
+                "Resource {}: Cannot retire, process {} still holds {} units.",
+                resource, pid, allocated
+            ),
+            BankersError::RequestCountMismatch { expected, found } => write!(
+                f,
+                "Expected a request row for each of the {} processes, got {}.",
+                expected, found
+            ),
+            BankersError::ProcessIdsExhausted => {
+                write!(f, "No process ids are left to hand out.")
+            }
+            BankersError::ResourceTypesLocked => write!(
+                f,
+                "Resource types cannot change while permits hold units of them."
+            ),
+        }
+    }
+}
+
+impl Error for BankersError {}
@@ -0,0 +1,227 @@
+use crate::BankersAlgorithm;
+
+/// Per resource type, the processes left after the first passes ordered by
+/// their need of it, and how far `work` already covers that order.
+struct Worklist {
+    by_need: Vec<Vec<(u64, usize)>>,
+    covered: Vec<usize>,
+    /// For each process, the number of resource types whose need `work` covers.
+    satisfied: Vec<usize>,
+    ready: Vec<usize>,
+}
+
+impl Worklist {
+    /// Moves the cursor of `resource` past every process whose need of it
+    /// now fits in `work`, queueing processes that are covered everywhere.
+    fn advance(&mut self, resource: usize, work: u64) {
+        let num_resources = self.by_need.len();
+        let order = &self.by_need[resource];
+        while let Some(&(need, index)) = order.get(self.covered[resource]) {
+            if need > work {
+                break;
+            }
+            self.covered[resource] += 1;
+            self.satisfied[index] += 1;
+            if self.satisfied[index] == num_resources {
+                self.ready.push(index);
+            }
+        }
+    }
+}
+
+impl BankersAlgorithm {
+    /// Gives the same verdict as [`is_safe_state`](Self::is_safe_state) in
+    /// O(n·m·log n) rather than O(n²·m) for n processes and m resource types.
+    ///
+    /// It starts with the same passes as `is_safe_state`, which settle most
+    /// states within a few. Once the passes reach log n, each resource type
+    /// keeps the processes left sorted by need, and finishing a process only
+    /// revisits the resource types it hands back. The safe sequence can then
+    /// differ from the one `is_safe_state` finds, but is just as valid.
+    pub fn is_safe_state_fast(&self) -> Option<Vec<usize>> {
+        let num_processes = self.processes.len();
+        let mut work = self.available.clone();
+        let mut safe_sequence = Vec::with_capacity(num_processes);
+        let mut left: Vec<usize> = (0..num_processes).collect();
+
+        let passes = usize::BITS - num_processes.leading_zeros();
+        for _ in 0..passes {
+            let before = left.len();
+            left.retain(|&index| {
+                let process = &self.processes[index];
+                let can_finish = process.need.iter().zip(&work).all(|(&n, &w)| n <= w);
+                if can_finish {
+                    for (w, &a) in work.iter_mut().zip(&process.allocation) {
+                        *w += a;
+                    }
+                    safe_sequence.push(process.id);
+                }
+                !can_finish
+            });
+            if left.is_empty() {
+                return Some(safe_sequence);
+            }
+            if left.len() == before {
+                return None;
+            }
+        }
+
+        let by_need = (0..self.resources.len())
+            .map(|k| {
+                let mut order: Vec<(u64, usize)> = left
+                    .iter()
+                    .map(|&index| (self.processes[index].need[k], index))
+                    .collect();
+                order.sort_unstable();
+                order
+            })
+            .collect();
+        let mut worklist = Worklist {
+            by_need,
+            covered: vec![0; self.resources.len()],
+            satisfied: vec![0; num_processes],
+            ready: Vec::new(),
+        };
+        for (k, &units) in work.iter().enumerate() {
+            worklist.advance(k, units);
+        }
+
+        while let Some(index) = worklist.ready.pop() {
+            let process = &self.processes[index];
+            safe_sequence.push(process.id);
+            for (k, &units) in process.allocation.iter().enumerate() {
+                if units > 0 {
+                    work[k] += units;
+                    worklist.advance(k, work[k]);
+                }
+            }
+        }
+
+        (safe_sequence.len() == num_processes).then_some(safe_sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BankersAlgorithm, Process};
+
+    /// Small linear congruential generator, enough to spread the states.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn up_to(&mut self, max: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (self.0 >> 33) % (max + 1)
+        }
+    }
+
+    fn random_state(rng: &mut Lcg) -> BankersAlgorithm {
+        let num_processes = rng.up_to(12) as usize;
+        let num_resources = 1 + rng.up_to(3) as usize;
+        let slack = rng.up_to(3);
+
+        let mut totals = vec![slack; num_resources];
+        let mut parts = Vec::with_capacity(num_processes);
+        for _ in 0..num_processes {
+            let allocation: Vec<u64> = (0..num_resources).map(|_| rng.up_to(3)).collect();
+            let extra: Vec<u64> = (0..num_resources).map(|_| rng.up_to(4)).collect();
+            for (total, &units) in totals.iter_mut().zip(&allocation) {
+                *total += units;
+            }
+            parts.push((allocation, extra));
+        }
+        let processes = parts
+            .into_iter()
+            .enumerate()
+            .map(|(pid, (allocation, extra))| {
+                let max_need = allocation
+                    .iter()
+                    .zip(&extra)
+                    .zip(&totals)
+                    .map(|((&units, &more), &total)| (units + more).min(total))
+                    .collect();
+                Process::new(pid, allocation, max_need).unwrap()
+            })
+            .collect();
+        BankersAlgorithm::from_parts(totals, processes).unwrap()
+    }
+
+    /// Each process holds one unit and needs one more than the process after
+    /// it, so the plain passes finish a single process each. With `broken`,
+    /// that process needs one unit too many and the chain stops there.
+    fn chain(num_processes: usize, broken: Option<usize>) -> BankersAlgorithm {
+        let n = num_processes as u64;
+        let processes = (0..num_processes)
+            .map(|pid| {
+                let max = 1 + n - pid as u64 + u64::from(broken == Some(pid));
+                Process::new(pid, vec![1], vec![max]).unwrap()
+            })
+            .collect();
+        BankersAlgorithm::from_parts(vec![n + 1], processes).unwrap()
+    }
+
+    /// Checks that both checkers agree, and that the fast one's sequence
+    /// lets every process finish in turn.
+    fn assert_agrees(state: &BankersAlgorithm) -> bool {
+        let naive = state.is_safe_state();
+        let fast = state.is_safe_state_fast();
+        assert_eq!(
+            naive.is_some(),
+            fast.is_some(),
+            "verdicts differ for {:?}",
+            state
+        );
+
+        if let Some(sequence) = &fast {
+            let mut work = state.available().to_vec();
+            for &pid in sequence {
+                let process = state.process(pid).unwrap();
+                assert!(
+                    process
+                        .need()
+                        .iter()
+                        .zip(&work)
+                        .all(|(need, work)| need <= work),
+                    "P{} cannot finish in {:?} for {:?}",
+                    pid,
+                    sequence,
+                    state
+                );
+                for (work, units) in work.iter_mut().zip(process.allocation()) {
+                    *work += units;
+                }
+            }
+            assert_eq!(sequence.len(), state.processes().len());
+        }
+        fast.is_some()
+    }
+
+    #[test]
+    fn agrees_on_random_states() {
+        let mut rng = Lcg(0x5EED);
+        let safe = (0..5_000)
+            .filter(|_| assert_agrees(&random_state(&mut rng)))
+            .count();
+        // Both verdicts are well represented.
+        assert!((500..4_500).contains(&safe), "{} safe states", safe);
+    }
+
+    #[test]
+    fn agrees_past_the_plain_passes() {
+        for num_processes in 0..100 {
+            assert!(assert_agrees(&chain(num_processes, None)));
+            for broken in 1..num_processes {
+                assert!(!assert_agrees(&chain(num_processes, Some(broken))));
+            }
+        }
+    }
+
+    #[test]
+    fn finishes_a_long_chain_in_order() {
+        let sequence = chain(1_000, None).is_safe_state_fast().unwrap();
+        assert_eq!(sequence, (0..1_000).rev().collect::<Vec<usize>>());
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt::Write;
+
+use crate::BankersAlgorithm;
+
+impl BankersAlgorithm {
+    /// Draws the resource-allocation graph in Graphviz DOT.
+    ///
+    /// Processes are circles and resource types are boxes labelled with
+    /// their total and free units. Solid edges run from a resource to the
+    /// processes holding it, weighted by allocation; dashed claim edges run
+    /// from a process to the resources it may still request, weighted by
+    /// need. Processes the safety check leaves blocked are filled in red.
+    pub fn to_dot(&self) -> String {
+        let blocked = self.check_safety().blocked_pids();
+        let mut dot = String::from("digraph banker {\n    rankdir=LR;\n");
+
+        for process in &self.processes {
+            let style = if blocked.contains(&process.id) {
+                ", style=filled, fillcolor=\"#f4cccc\", color=\"#cc0000\""
+            } else {
+                ""
+            };
+            writeln!(dot, "    P{} [shape=circle{}];", process.id, style).unwrap();
+        }
+        for (k, (total, available)) in self.resources.iter().zip(&self.available).enumerate() {
+            writeln!(
+                dot,
+                "    R{} [shape=box, label=\"R{}\\n{} units, {} free\"];",
+                k, k, total, available
+            )
+            .unwrap();
+        }
+        for process in &self.processes {
+            for (k, (&allocation, &need)) in
+                process.allocation.iter().zip(&process.need).enumerate()
+            {
+                if allocation > 0 {
+                    writeln!(
+                        dot,
+                        "    R{} -> P{} [label=\"{}\"];",
+                        k, process.id, allocation
+                    )
+                    .unwrap();
+                }
+                if need > 0 {
+                    writeln!(
+                        dot,
+                        "    P{} -> R{} [label=\"{}\", style=dashed];",
+                        process.id, k, need
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Draws the same graph as [`to_dot`](Self::to_dot) as a Mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        let blocked = self.check_safety().blocked_pids();
+        let mut mermaid = String::from("flowchart LR\n");
+
+        for process in &self.processes {
+            writeln!(mermaid, "    P{}((P{}))", process.id, process.id).unwrap();
+        }
+        for (k, (total, available)) in self.resources.iter().zip(&self.available).enumerate() {
+            writeln!(
+                mermaid,
+                "    R{}[\"R{}<br/>{} units, {} free\"]",
+                k, k, total, available
+            )
+            .unwrap();
+        }
+        for process in &self.processes {
+            for (k, (&allocation, &need)) in
+                process.allocation.iter().zip(&process.need).enumerate()
+            {
+                if allocation > 0 {
+                    writeln!(mermaid, "    R{} -->|{}| P{}", k, allocation, process.id).unwrap();
+                }
+                if need > 0 {
+                    writeln!(mermaid, "    P{} -.->|{}| R{}", process.id, need, k).unwrap();
+                }
+            }
+        }
+        if !blocked.is_empty() {
+            let blocked: Vec<String> = blocked.iter().map(|pid| format!("P{}", pid)).collect();
+            mermaid.push_str("    classDef blocked fill:#f4cccc,stroke:#cc0000\n");
+            writeln!(mermaid, "    class {} blocked", blocked.join(",")).unwrap();
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{state, textbook};
+
+    /// Once P2 finishes, P0 and P1 both need 2 units of R0 with 1 free.
+    fn unsafe_state() -> BankersAlgorithm {
+        state(
+            &[4, 2],
+            &[(&[2, 0], &[4, 1]), (&[1, 1], &[3, 2]), (&[1, 0], &[1, 0])],
+        )
+    }
+
+    #[test]
+    fn dot_draws_edges_labels_and_blocked_processes() {
+        let dot = unsafe_state().to_dot();
+        assert_eq!(
+            dot.lines().collect::<Vec<_>>(),
+            vec![
+                "digraph banker {",
+                "    rankdir=LR;",
+                "    P0 [shape=circle, style=filled, fillcolor=\"#f4cccc\", color=\"#cc0000\"];",
+                "    P1 [shape=circle, style=filled, fillcolor=\"#f4cccc\", color=\"#cc0000\"];",
+                "    P2 [shape=circle];",
+                "    R0 [shape=box, label=\"R0\\n4 units, 0 free\"];",
+                "    R1 [shape=box, label=\"R1\\n2 units, 1 free\"];",
+                "    R0 -> P0 [label=\"2\"];",
+                "    P0 -> R0 [label=\"2\", style=dashed];",
+                "    P0 -> R1 [label=\"1\", style=dashed];",
+                "    R0 -> P1 [label=\"1\"];",
+                "    P1 -> R0 [label=\"2\", style=dashed];",
+                "    R1 -> P1 [label=\"1\"];",
+                "    P1 -> R1 [label=\"1\", style=dashed];",
+                "    R0 -> P2 [label=\"1\"];",
+                "}",
+            ]
+        );
+        assert!(!textbook().to_dot().contains("style=filled"));
+    }
+
+    #[test]
+    fn mermaid_draws_edges_labels_and_blocked_processes() {
+        let mermaid = unsafe_state().to_mermaid();
+        assert_eq!(
+            mermaid.lines().collect::<Vec<_>>(),
+            vec![
+                "flowchart LR",
+                "    P0((P0))",
+                "    P1((P1))",
+                "    P2((P2))",
+                "    R0[\"R0<br/>4 units, 0 free\"]",
+                "    R1[\"R1<br/>2 units, 1 free\"]",
+                "    R0 -->|2| P0",
+                "    P0 -.->|2| R0",
+                "    P0 -.->|1| R1",
+                "    R0 -->|1| P1",
+                "    P1 -.->|2| R0",
+                "    R1 -->|1| P1",
+                "    P1 -.->|1| R1",
+                "    R0 -->|1| P2",
+                "    classDef blocked fill:#f4cccc,stroke:#cc0000",
+                "    class P0,P1 blocked",
+            ]
+        );
+        assert!(!textbook().to_mermaid().contains("blocked"));
+    }
+}
@@ -0,0 +1,54 @@
+//! Banker's algorithm for deadlock avoidance.
+//!
+//! Build a system state with [`BankersAlgorithm::from_parts`], query it with
+//! [`BankersAlgorithm::is_safe_state`], hand out resources through
+//! [`BankersAlgorithm::request`] and take them back with
+//! [`BankersAlgorithm::release`] or [`BankersAlgorithm::finish`]. Grants
+//! check safety with [`BankersAlgorithm::is_safe_state_fast`], which scales
+//! to thousands of processes. New processes join through
+//! [`BankersAlgorithm::admit`]. Requests that cannot be granted yet can wait
+//! in a queue, see [`BankersAlgorithm::request_or_queue`], and
+//! [`SharedBanker`] lets threads or async tasks wait until their requests can
+//! be granted.
+//!
+//! Besides avoidance, [`BankersAlgorithm::detect_deadlock`] finds the
+//! processes that are deadlocked given what each is waiting for now, and
+//! [`BankersAlgorithm::plan_recovery`] picks victims to get out of it.
+//! [`Workload`] drives the banker with a seeded random workload over time.
+//!
+//! States can also be read from JSON, TOML or textbook matrix files, see
+//! [`Scenario`], and drawn as resource-allocation graphs with
+//! [`BankersAlgorithm::to_dot`] or [`BankersAlgorithm::to_mermaid`].
+
+mod admission;
+mod banker;
+mod capacity;
+mod detection;
+mod error;
+mod fast_safety;
+mod graph;
+mod matrix;
+mod process;
+mod queue;
+mod recovery;
+mod request;
+mod safety;
+mod scenario;
+mod sequences;
+mod shared;
+#[cfg(test)]
+mod testing;
+mod workload;
+
+pub use banker::BankersAlgorithm;
+pub use capacity::Retirement;
+pub use error::BankersError;
+pub use matrix::ParseError;
+pub use process::Process;
+pub use queue::{PendingRequest, QueuePolicy, Queued, Wakeup};
+pub use recovery::{Recovery, RecoveryCost, RecoveryPlan};
+pub use request::{Denial, Grant, Release};
+pub use safety::{BlockedProcess, SafetyReport, Shortfall, TraceStep};
+pub use scenario::{LoadError, Scenario, ScenarioProcess};
+pub use shared::{Acquire, Permit, SharedBanker};
+pub use workload::{UtilizationSample, Workload, WorkloadReport};
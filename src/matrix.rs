@@ -0,0 +1,529 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{LoadError, Scenario, ScenarioProcess};
+
+/// A syntax or layout problem in a matrix text file, with a 1-based position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Totals,
+    Available,
+    Allocation,
+    Max,
+    Request,
+}
+
+impl Section {
+    fn from_header(header: &str) -> Option<Section> {
+        match header.trim_end_matches(':').to_lowercase().as_str() {
+            "resources" | "total" | "totals" => Some(Section::Totals),
+            "available" => Some(Section::Available),
+            "allocation" => Some(Section::Allocation),
+            "max" => Some(Section::Max),
+            "request" | "requests" => Some(Section::Request),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Section::Totals => "Resources",
+            Section::Available => "Available",
+            Section::Allocation => "Allocation",
+            Section::Max => "Max",
+            Section::Request => "Request",
+        }
+    }
+}
+
+struct Row {
+    line: usize,
+    column: usize,
+    label: Option<usize>,
+    values: Vec<u64>,
+}
+
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let content = line.split('#').next().unwrap_or_default();
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (column, (offset, c)) in content.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(offset),
+            (true, Some(begin)) => {
+                let token = &content[begin..offset];
+                tokens.push((column + 1 - token.chars().count(), token));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        let token = &content[begin..];
+        let column = content.chars().count() + 1 - token.chars().count();
+        tokens.push((column, token));
+    }
+    tokens
+}
+
+fn process_label(token: &str) -> Option<usize> {
+    token
+        .strip_prefix(['P', 'p'])
+        .and_then(|digits| digits.trim_end_matches(':').parse().ok())
+}
+
+fn parse_row(line: usize, tokens: &[(usize, &str)]) -> Result<Row, ParseError> {
+    let (label, values) = match tokens.first() {
+        Some(&(_, first)) if process_label(first).is_some() => (process_label(first), &tokens[1..]),
+        _ => (None, tokens),
+    };
+
+    let column = values.first().map_or(tokens[0].0, |&(column, _)| column);
+    let values = values
+        .iter()
+        .map(|&(column, token)| {
+            token.parse::<u64>().map_err(|_| {
+                ParseError::new(
+                    line,
+                    column,
+                    format!("Invalid number '{}'. Expected a unit count.", token),
+                )
+            })
+        })
+        .collect::<Result<Vec<u64>, ParseError>>()?;
+
+    Ok(Row {
+        line,
+        column,
+        label,
+        values,
+    })
+}
+
+impl Scenario {
+    /// Parses the plain-text matrix format used in textbooks.
+    ///
+    /// ```text
+    /// Resources: 10 5 7
+    /// Allocation
+    /// P0  0 1 0
+    /// P1  2 0 0
+    /// Max
+    /// P0  7 5 3
+    /// P1  3 2 2
+    /// ```
+    ///
+    /// The header of the first line may be left out. `Available` can be given
+    /// in place of `Resources`, in which case the totals are derived from the
+    /// allocations. An optional `Request` section lists what each process is
+    /// waiting for. Row labels are optional and `#` starts a comment.
+    pub fn from_matrix(input: &str) -> Result<Scenario, LoadError> {
+        Ok(parse_matrix(input)?)
+    }
+
+    /// Formats the scenario in the matrix text format, one labelled row per process.
+    pub fn to_matrix(&self) -> String {
+        let row = |values: &[u64]| {
+            let values: Vec<String> = values.iter().map(u64::to_string).collect();
+            values.join(" ")
+        };
+        let label = |index: usize, process: &ScenarioProcess| process.id.unwrap_or(index);
+
+        let mut text = format!("Resources: {}\n", row(&self.resources));
+        text.push_str("Allocation\n");
+        for (index, process) in self.processes.iter().enumerate() {
+            text.push_str(&format!(
+                "P{}  {}\n",
+                label(index, process),
+                row(&process.allocation)
+            ));
+        }
+        text.push_str("Max\n");
+        for (index, process) in self.processes.iter().enumerate() {
+            text.push_str(&format!(
+                "P{}  {}\n",
+                label(index, process),
+                row(&process.max)
+            ));
+        }
+        if self.processes.iter().any(|p| p.request.is_some()) {
+            text.push_str("Request\n");
+            for ((index, process), request) in
+                self.processes.iter().enumerate().zip(self.requests())
+            {
+                text.push_str(&format!("P{}  {}\n", label(index, process), row(&request)));
+            }
+        }
+        text
+    }
+}
+
+fn parse_matrix(input: &str) -> Result<Scenario, ParseError> {
+    let mut section = Section::Totals;
+    let mut seen: Vec<Section> = Vec::new();
+    let mut totals: Option<Row> = None;
+    let mut available: Option<Row> = None;
+    let mut allocation: Vec<Row> = Vec::new();
+    let mut max: Vec<Row> = Vec::new();
+    let mut request: Vec<Row> = Vec::new();
+    let mut last_line = 0;
+
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        let tokens = tokenize(text);
+        let Some(&(column, first)) = tokens.first() else {
+            continue;
+        };
+        last_line = line;
+
+        let mut row_tokens = &tokens[..];
+        if first.starts_with(|c: char| c.is_alphabetic()) && process_label(first).is_none() {
+            let header = Section::from_header(first).ok_or_else(|| {
+                ParseError::new(
+                    line,
+                    column,
+                    format!(
+                        "Unknown section '{}'. Expected Resources, Available, Allocation, Max or Request.",
+                        first
+                    ),
+                )
+            })?;
+            if seen.contains(&header) {
+                return Err(ParseError::new(
+                    line,
+                    column,
+                    format!("Duplicate {} section.", header.name()),
+                ));
+            }
+            seen.push(header);
+            section = header;
+            row_tokens = &tokens[1..];
+            if row_tokens.is_empty() {
+                continue;
+            }
+        } else if seen.is_empty() {
+            seen.push(Section::Totals);
+        }
+
+        let row = parse_row(line, row_tokens)?;
+        match section {
+            Section::Totals | Section::Available => {
+                let slot = if section == Section::Totals {
+                    &mut totals
+                } else {
+                    &mut available
+                };
+                if slot.is_some() {
+                    return Err(ParseError::new(
+                        line,
+                        column,
+                        format!(
+                            "Expected a section header. The {} section takes a single row.",
+                            section.name()
+                        ),
+                    ));
+                }
+                *slot = Some(row);
+            }
+            Section::Allocation => allocation.push(row),
+            Section::Max => max.push(row),
+            Section::Request => request.push(row),
+        }
+    }
+
+    let end = last_line + 1;
+    let (base, derive_totals) = match (totals, available) {
+        (Some(_), Some(available)) => {
+            return Err(ParseError::new(
+                available.line,
+                available.column,
+                "Give either Resources or Available, not both.",
+            ));
+        }
+        (Some(totals), None) => (totals, false),
+        (None, Some(available)) => (available, true),
+        (None, None) => return Err(ParseError::new(end, 1, "Missing Resources line.")),
+    };
+    if !seen.contains(&Section::Allocation) {
+        return Err(ParseError::new(end, 1, "Missing Allocation section."));
+    }
+    if !seen.contains(&Section::Max) {
+        return Err(ParseError::new(end, 1, "Missing Max section."));
+    }
+    if allocation.len() != max.len() {
+        let extra = if allocation.len() > max.len() {
+            &allocation[max.len()]
+        } else {
+            &max[allocation.len()]
+        };
+        return Err(ParseError::new(
+            extra.line,
+            extra.column,
+            format!(
+                "Allocation has {} rows but Max has {}.",
+                allocation.len(),
+                max.len()
+            ),
+        ));
+    }
+
+    if seen.contains(&Section::Request) && request.len() != allocation.len() {
+        let (line, column) = request
+            .get(allocation.len())
+            .map_or((end, 1), |extra| (extra.line, extra.column));
+        return Err(ParseError::new(
+            line,
+            column,
+            format!(
+                "Allocation has {} rows but Request has {}.",
+                allocation.len(),
+                request.len()
+            ),
+        ));
+    }
+
+    let num_resources = base.values.len();
+    for row in allocation.iter().chain(&max).chain(&request) {
+        if row.values.len() != num_resources {
+            return Err(ParseError::new(
+                row.line,
+                row.column,
+                format!(
+                    "Expected {} values, found {}.",
+                    num_resources,
+                    row.values.len()
+                ),
+            ));
+        }
+    }
+
+    let mut resources = base.values;
+    if derive_totals {
+        for (k, total) in resources.iter_mut().enumerate() {
+            let sum = allocation
+                .iter()
+                .try_fold(*total, |sum, row| sum.checked_add(row.values[k]));
+            *total = sum.ok_or_else(|| {
+                ParseError::new(
+                    base.line,
+                    base.column,
+                    format!("Total units of resource {} exceed {}.", k, u64::MAX),
+                )
+            })?;
+        }
+    }
+
+    let mut requests = request.into_iter();
+    let mut processes = Vec::with_capacity(allocation.len());
+    for (alloc_row, max_row) in allocation.into_iter().zip(max) {
+        let request_row = requests.next();
+        for (name, row) in [("Max", Some(&max_row)), ("Request", request_row.as_ref())] {
+            if let (Some(a), Some(row)) = (alloc_row.label, row)
+                && let Some(label) = row.label
+                && a != label
+            {
+                return Err(ParseError::new(
+                    row.line,
+                    1,
+                    format!(
+                        "{} row for P{} does not match Allocation row for P{}.",
+                        name, label, a
+                    ),
+                ));
+            }
+        }
+        processes.push(ScenarioProcess {
+            id: alloc_row.label.or(max_row.label),
+            allocation: alloc_row.values,
+            max: max_row.values,
+            request: request_row.map(|row| row.values),
+        });
+    }
+
+    Ok(Scenario {
+        resources,
+        processes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXTBOOK: &str = "\
+Resources: 10 5 7
+Allocation
+P0  0 1 0   # waiting for its claim
+P1  2 0 0
+Max
+P0  7 5 3
+P1  3 2 2
+";
+
+    fn error_at(input: &str) -> (usize, usize, String) {
+        let error = parse_matrix(input).unwrap_err();
+        (error.line, error.column, error.message)
+    }
+
+    #[test]
+    fn parses_labelled_rows_and_comments() {
+        let scenario = parse_matrix(TEXTBOOK).unwrap();
+        assert_eq!(scenario.resources, vec![10, 5, 7]);
+        assert_eq!(scenario.processes.len(), 2);
+        assert_eq!(scenario.processes[0].id, Some(0));
+        assert_eq!(scenario.processes[0].allocation, vec![0, 1, 0]);
+        assert_eq!(scenario.processes[1].max, vec![3, 2, 2]);
+        assert_eq!(scenario.processes[1].request, None);
+    }
+
+    #[test]
+    fn first_header_and_labels_are_optional() {
+        let scenario = parse_matrix("# totals\n10 5 7\nAllocation\n0 1 0\nMax\n7 5 3\n").unwrap();
+        assert_eq!(scenario.resources, vec![10, 5, 7]);
+        assert_eq!(scenario.processes[0].id, None);
+        assert_eq!(scenario.processes[0].max, vec![7, 5, 3]);
+    }
+
+    #[test]
+    fn available_derives_the_totals() {
+        let input = TEXTBOOK.replace("Resources: 10 5 7", "Available: 3 3 2");
+        let scenario = parse_matrix(&input).unwrap();
+        assert_eq!(scenario.resources, vec![5, 4, 2]);
+    }
+
+    #[test]
+    fn resources_and_available_are_exclusive() {
+        let input = TEXTBOOK.replace("Allocation", "Available 3 3 2\nAllocation");
+        assert_eq!(
+            error_at(&input),
+            (
+                2,
+                11,
+                "Give either Resources or Available, not both.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_section_points_at_its_header() {
+        let input = format!("{}  Max\nP0 1 1 1\n", TEXTBOOK);
+        assert_eq!(
+            error_at(&input),
+            (8, 3, "Duplicate Max section.".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_section_is_rejected() {
+        let (line, column, message) = error_at("Resources: 1\nAllocations\n");
+        assert_eq!((line, column), (2, 1));
+        assert!(message.starts_with("Unknown section 'Allocations'."));
+    }
+
+    #[test]
+    fn bad_number_points_at_the_token() {
+        let input = TEXTBOOK.replace("P1  2 0 0", "  P1  2 x 0");
+        assert_eq!(
+            error_at(&input),
+            (
+                4,
+                9,
+                "Invalid number 'x'. Expected a unit count.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn short_row_points_at_its_first_value() {
+        let input = TEXTBOOK.replace("P1  3 2 2", "P1\t 3 2");
+        assert_eq!(
+            error_at(&input),
+            (7, 5, "Expected 3 values, found 2.".to_string())
+        );
+    }
+
+    #[test]
+    fn row_count_mismatch_points_at_the_extra_row() {
+        let input = TEXTBOOK.replace("P1  3 2 2\n", "");
+        assert_eq!(
+            error_at(&input),
+            (4, 5, "Allocation has 2 rows but Max has 1.".to_string())
+        );
+    }
+
+    #[test]
+    fn mismatched_labels_are_rejected() {
+        let input = TEXTBOOK.replace("P1  3 2 2", "P3  3 2 2");
+        assert_eq!(
+            error_at(&input),
+            (
+                7,
+                1,
+                "Max row for P3 does not match Allocation row for P1.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn missing_sections_point_past_the_end() {
+        assert_eq!(
+            error_at("Resources: 1\nAllocation\nP0 0\n\n"),
+            (4, 1, "Missing Max section.".to_string())
+        );
+        assert_eq!(
+            error_at("Allocation\nP0 0\nMax\nP0 1\n"),
+            (5, 1, "Missing Resources line.".to_string())
+        );
+    }
+
+    #[test]
+    fn request_rows_must_match_allocation() {
+        let input = format!("{}Request\nP0 1 0 0\n", TEXTBOOK);
+        assert_eq!(
+            error_at(&input),
+            (
+                10,
+                1,
+                "Allocation has 2 rows but Request has 1.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn to_matrix_round_trips() {
+        let input = format!("{}Request\nP0 1 0 0\nP1 0 0 1\n", TEXTBOOK);
+        let scenario = parse_matrix(&input).unwrap();
+        assert_eq!(scenario.processes[1].request, Some(vec![0, 0, 1]));
+        assert_eq!(parse_matrix(&scenario.to_matrix()).unwrap(), scenario);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::BankersError;
+
+/// A process with its current allocation and declared maximum claim.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub(crate) id: usize,
+    pub(crate) allocation: Vec<u64>,
+    pub(crate) max_need: Vec<u64>,
+    pub(crate) need: Vec<u64>,
+}
+
+impl Process {
+    pub fn new(
+        id: usize,
+        allocation: Vec<u64>,
+        max_need: Vec<u64>,
+    ) -> Result<Process, BankersError> {
+        if allocation.len() != max_need.len() {
+            return Err(BankersError::LengthMismatch {
+                pid: id,
+                allocation: allocation.len(),
+                max_need: max_need.len(),
+            });
+        }
+        let mut need: Vec<u64> = Vec::with_capacity(allocation.len());
+        for i in 0..allocation.len() {
+            if allocation[i] > max_need[i] {
+                return Err(BankersError::AllocationExceedsMax {
+                    pid: id,
+                    resource: i,
+                    allocation: allocation[i],
+                    max: max_need[i],
+                });
+            }
+            need.push(max_need[i] - allocation[i]);
+        }
+        Ok(Process {
+            id,
+            allocation,
+            max_need,
+            need,
+        })
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn allocation(&self) -> &[u64] {
+        &self.allocation
+    }
+
+    pub fn max_need(&self) -> &[u64] {
+        &self.max_need
+    }
+
+    /// Remaining claim, `max_need - allocation`.
+    pub fn need(&self) -> &[u64] {
+        &self.need
+    }
+}
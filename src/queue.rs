@@ -0,0 +1,266 @@
+use crate::{BankersAlgorithm, Denial, Grant};
+
+/// Order in which queued requests are retried once units come back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueuePolicy {
+    /// Oldest request first.
+    #[default]
+    Fifo,
+    /// The process with the least remaining need first, oldest first on ties.
+    ShortestNeedFirst,
+    /// Highest priority first, oldest first on ties.
+    Priority,
+}
+
+/// A request waiting in the queue, identified by its ticket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub ticket: u64,
+    pub pid: usize,
+    pub amount: Vec<u64>,
+    pub priority: u64,
+}
+
+/// What became of a request made through
+/// [`request_or_queue`](BankersAlgorithm::request_or_queue).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Queued {
+    Granted(Grant),
+    /// The request waits in the queue under this ticket.
+    Waiting(u64),
+}
+
+/// A queued request that left the queue when it was retried: granted, or
+/// refused because it can no longer be granted at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wakeup {
+    pub ticket: u64,
+    pub outcome: Result<Grant, Denial>,
+}
+
+impl BankersAlgorithm {
+    pub fn queue_policy(&self) -> QueuePolicy {
+        self.queue_policy
+    }
+
+    pub fn set_queue_policy(&mut self, policy: QueuePolicy) {
+        self.queue_policy = policy;
+    }
+
+    /// Queued requests, oldest first.
+    pub fn pending(&self) -> &[PendingRequest] {
+        &self.pending
+    }
+
+    /// Grants `amount` to process `pid` like [`request`](Self::request),
+    /// but queues it if the units are not available or granting them now
+    /// would be unsafe. `priority` only matters under [`QueuePolicy::Priority`].
+    ///
+    /// Queued requests are retried whenever units are released, see
+    /// [`retry_pending`](Self::retry_pending).
+    pub fn request_or_queue(
+        &mut self,
+        pid: usize,
+        amount: &[u64],
+        priority: u64,
+    ) -> Result<Queued, Denial> {
+        match self.request(pid, amount) {
+            Ok(grant) => Ok(Queued::Granted(grant)),
+            Err(Denial::Unavailable { .. } | Denial::Unsafe { .. }) => {
+                let ticket = self.next_ticket;
+                self.next_ticket += 1;
+                self.pending.push(PendingRequest {
+                    ticket,
+                    pid,
+                    amount: amount.to_vec(),
+                    priority,
+                });
+                Ok(Queued::Waiting(ticket))
+            }
+            Err(denial) => Err(denial),
+        }
+    }
+
+    /// Takes a request out of the queue, e.g. when its waiter gives up.
+    pub fn cancel(&mut self, ticket: u64) -> Option<PendingRequest> {
+        let index = self.pending.iter().position(|p| p.ticket == ticket)?;
+        Some(self.pending.remove(index))
+    }
+
+    /// Retries every queued request in the order of the queue policy and
+    /// returns the ones that left the queue.
+    ///
+    /// [`release`](Self::release) and [`finish`](Self::finish) call this
+    /// already; call it after other changes that free up units or lower
+    /// claims, such as [`add_capacity`](Self::add_capacity) or
+    /// [`revise_max`](Self::revise_max). Requests that now exceed the
+    /// process's need come back refused.
+    pub fn retry_pending(&mut self) -> Vec<Wakeup> {
+        let mut order: Vec<usize> = (0..self.pending.len()).collect();
+        match self.queue_policy {
+            QueuePolicy::Fifo => {}
+            QueuePolicy::ShortestNeedFirst => order.sort_by_key(|&i| {
+                self.process(self.pending[i].pid).map_or(0, |p| {
+                    p.need.iter().fold(0u64, |sum, &n| sum.saturating_add(n))
+                })
+            }),
+            QueuePolicy::Priority => {
+                order.sort_by_key(|&i| std::cmp::Reverse(self.pending[i].priority))
+            }
+        }
+
+        let mut queue: Vec<Option<PendingRequest>> = std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut woken = Vec::new();
+        for i in order {
+            let pending = queue[i].as_ref().expect("each request is retried once");
+            let outcome = match self.request(pending.pid, &pending.amount) {
+                Err(Denial::Unavailable { .. } | Denial::Unsafe { .. }) => continue,
+                outcome => outcome,
+            };
+            woken.push(Wakeup {
+                ticket: pending.ticket,
+                outcome,
+            });
+            queue[i] = None;
+        }
+
+        self.pending = queue.into_iter().flatten().collect();
+        self.record_woken(&woken);
+        woken
+    }
+
+    /// Keeps requests that left the queue for a
+    /// [`SharedBanker`](crate::SharedBanker), if one is recording them.
+    pub(crate) fn record_woken(&mut self, woken: &[Wakeup]) {
+        if let Some(recorded) = &mut self.woken {
+            recorded.extend_from_slice(woken);
+        }
+    }
+
+    /// Drops the queued requests of a process that is leaving.
+    pub(crate) fn drop_pending(&mut self, pid: usize) {
+        self.pending.retain(|p| p.pid != pid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::state;
+
+    /// P0 and P4 hold all three units and need no more. P1, P2 and P3 each
+    /// wait for one unit, with needs 3, 1 and 2 and priorities 1, 0 and 9.
+    fn queued(policy: QueuePolicy) -> BankersAlgorithm {
+        let mut banker = state(
+            &[3],
+            &[
+                (&[1], &[1]),
+                (&[0], &[3]),
+                (&[0], &[1]),
+                (&[0], &[2]),
+                (&[2], &[2]),
+            ],
+        );
+        banker.set_queue_policy(policy);
+        for (pid, priority) in [(1, 1), (2, 0), (3, 9)] {
+            assert_eq!(
+                banker.request_or_queue(pid, &[1], priority),
+                Ok(Queued::Waiting(pid as u64 - 1))
+            );
+        }
+        banker
+    }
+
+    /// Has P0 free its unit and returns the tickets that were granted, and
+    /// those still waiting.
+    fn release_one(mut banker: BankersAlgorithm) -> (Vec<u64>, Vec<u64>) {
+        let release = banker.release(0, &[1]).unwrap();
+        let granted = release
+            .woken
+            .iter()
+            .map(|wakeup| {
+                assert!(wakeup.outcome.is_ok());
+                wakeup.ticket
+            })
+            .collect();
+        let waiting = banker.pending().iter().map(|p| p.ticket).collect();
+        (granted, waiting)
+    }
+
+    #[test]
+    fn fifo_serves_the_oldest_request() {
+        assert_eq!(
+            release_one(queued(QueuePolicy::Fifo)),
+            (vec![0], vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn shortest_need_first_serves_the_smallest_need() {
+        assert_eq!(
+            release_one(queued(QueuePolicy::ShortestNeedFirst)),
+            (vec![1], vec![0, 2])
+        );
+    }
+
+    #[test]
+    fn priority_serves_the_highest_priority() {
+        assert_eq!(
+            release_one(queued(QueuePolicy::Priority)),
+            (vec![2], vec![0, 1])
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_oldest_request() {
+        let mut banker = queued(QueuePolicy::ShortestNeedFirst);
+        banker.cancel(1);
+        banker.revise_max(1, &[2]).unwrap();
+        assert_eq!(release_one(banker), (vec![0], vec![2]));
+    }
+
+    #[test]
+    fn requests_that_can_never_be_granted_are_not_queued() {
+        let mut banker = queued(QueuePolicy::Fifo);
+        assert_eq!(
+            banker.request_or_queue(2, &[2], 0),
+            Err(Denial::ExceedsNeed {
+                pid: 2,
+                resource: 0,
+                requested: 2,
+                need: 1,
+            })
+        );
+        assert_eq!(banker.pending().len(), 3);
+    }
+
+    #[test]
+    fn cancel_and_finish_take_requests_out() {
+        let mut banker = queued(QueuePolicy::Fifo);
+        assert_eq!(banker.cancel(1).map(|p| p.pid), Some(2));
+        assert_eq!(banker.cancel(1), None);
+
+        banker.finish(3).unwrap();
+        let tickets: Vec<u64> = banker.pending().iter().map(|p| p.ticket).collect();
+        assert_eq!(tickets, vec![0]);
+    }
+
+    #[test]
+    fn retry_refuses_requests_beyond_the_new_need() {
+        let mut banker = queued(QueuePolicy::Fifo);
+        banker.revise_max(1, &[0]).unwrap();
+        banker.add_capacity(0, 3).unwrap();
+
+        let woken = banker.retry_pending();
+        assert_eq!(woken.len(), 3);
+        assert!(matches!(
+            woken[0].outcome,
+            Err(Denial::ExceedsNeed { pid: 1, .. })
+        ));
+        assert!(woken[1..].iter().all(|wakeup| wakeup.outcome.is_ok()));
+        assert!(banker.pending().is_empty());
+    }
+}
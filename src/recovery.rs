@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+
+use crate::{BankersAlgorithm, Process};
+
+/// What happens to the processes chosen as victims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Terminate the victims, releasing what they hold and removing them
+    /// along with their queued requests.
+    Terminate,
+    /// Take back everything the victims hold. They stay in the system and
+    /// start over with their full claim as need. A victim that claims more
+    /// than the totals could never finish that way, so it is terminated.
+    Preempt,
+}
+
+/// How expensive it is to pick a process as a victim. Plans minimize the
+/// total cost, then the number of victims.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryCost {
+    /// Every victim costs 1.
+    FewestProcesses,
+    /// A victim costs the number of units it holds.
+    LeastHeld,
+    /// A victim costs its priority. Processes missing from the map cost 0.
+    Priority(HashMap<usize, u64>),
+}
+
+/// The victims that bring the state back to safety, and what that gives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPlan {
+    pub recovery: Recovery,
+    pub victims: Vec<usize>,
+    pub cost: u64,
+    /// Units returned to the available pool.
+    pub reclaimed: Vec<u64>,
+    /// Safe sequence of the state once the plan is carried out.
+    pub safe_sequence: Vec<usize>,
+}
+
+impl RecoveryCost {
+    fn of(&self, process: &Process) -> u64 {
+        match self {
+            RecoveryCost::FewestProcesses => 1,
+            RecoveryCost::LeastHeld => process
+                .allocation
+                .iter()
+                .fold(0, |sum: u64, &units| sum.saturating_add(units)),
+            RecoveryCost::Priority(priorities) => priorities.get(&process.id).copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Most blocked processes [`BankersAlgorithm::plan_recovery`] searches
+/// exhaustively. The search is exponential in their number.
+const EXACT_SEARCH_LIMIT: usize = 12;
+
+/// Candidate victims with their cost, the combination being tried and the
+/// cheapest one found so far, all by process id.
+struct Search {
+    candidates: Vec<(usize, u64)>,
+    chosen: Vec<usize>,
+    best: Option<(u64, Vec<usize>)>,
+}
+
+impl BankersAlgorithm {
+    /// Finds the cheapest set of victims whose termination or preemption
+    /// makes the state safe. A safe state needs no victims.
+    ///
+    /// Only processes the safety check leaves blocked are considered, since
+    /// the others would hand back their units anyway. The search tries
+    /// every combination of them, pruned by the cheapest plan found so far,
+    /// which takes time exponential in their number. Beyond 12 blocked
+    /// processes it settles for a greedy plan instead: victims are taken
+    /// cheapest first until the state is safe, then those that turn out to
+    /// be unnecessary are spared. That plan is not always the cheapest.
+    ///
+    /// A deadlocked state is unsafe too, so this recovers from deadlock as well.
+    pub fn plan_recovery(&self, recovery: Recovery, cost: &RecoveryCost) -> RecoveryPlan {
+        let blocked = self.check_safety().blocked_pids();
+        let candidates: Vec<(usize, u64)> = self
+            .processes
+            .iter()
+            .filter(|p| blocked.contains(&p.id))
+            .map(|p| (p.id, cost.of(p)))
+            .collect();
+
+        let (cost, victims) = if candidates.len() <= EXACT_SEARCH_LIMIT {
+            let mut search = Search {
+                candidates,
+                chosen: Vec::new(),
+                best: None,
+            };
+            self.search_victims(recovery, &mut search, 0, 0);
+            search
+                .best
+                .expect("sacrificing every blocked process restores safety")
+        } else {
+            self.greedy_victims(recovery, candidates)
+        };
+
+        let mut recovered = self.clone();
+        let before = recovered.available.clone();
+        recovered.sacrifice(recovery, &victims);
+        let reclaimed = recovered
+            .available
+            .iter()
+            .zip(&before)
+            .map(|(after, before)| after - before)
+            .collect();
+
+        RecoveryPlan {
+            recovery,
+            victims,
+            cost,
+            reclaimed,
+            safe_sequence: recovered.is_safe_state().expect("recovered state is safe"),
+        }
+    }
+
+    /// Carries out [`plan_recovery`](Self::plan_recovery) on this state.
+    pub fn recover(&mut self, recovery: Recovery, cost: &RecoveryCost) -> RecoveryPlan {
+        let plan = self.plan_recovery(recovery, cost);
+        self.sacrifice(recovery, &plan.victims);
+        plan
+    }
+
+    fn search_victims(&self, recovery: Recovery, search: &mut Search, next: usize, cost: u64) {
+        if let Some((best_cost, best_victims)) = &search.best
+            && (cost, search.chosen.len()) >= (*best_cost, best_victims.len())
+        {
+            return;
+        }
+
+        if self.safe_without(recovery, &search.chosen) {
+            search.best = Some((cost, search.chosen.clone()));
+            return;
+        }
+
+        for position in next..search.candidates.len() {
+            let (pid, victim_cost) = search.candidates[position];
+            search.chosen.push(pid);
+            self.search_victims(
+                recovery,
+                search,
+                position + 1,
+                cost.saturating_add(victim_cost),
+            );
+            search.chosen.pop();
+        }
+    }
+
+    fn greedy_victims(
+        &self,
+        recovery: Recovery,
+        mut candidates: Vec<(usize, u64)>,
+    ) -> (u64, Vec<usize>) {
+        candidates.sort_by_key(|&(_, cost)| cost);
+
+        let mut victims = Vec::new();
+        for &(pid, _) in &candidates {
+            if self.safe_without(recovery, &victims) {
+                break;
+            }
+            victims.push(pid);
+        }
+
+        // Spare the most expensive victims first.
+        for position in (0..victims.len()).rev() {
+            let pid = victims.remove(position);
+            if !self.safe_without(recovery, &victims) {
+                victims.insert(position, pid);
+            }
+        }
+
+        let cost = candidates
+            .iter()
+            .filter(|(pid, _)| victims.contains(pid))
+            .fold(0, |sum: u64, &(_, cost)| sum.saturating_add(cost));
+        // In process order, like the exhaustive search.
+        victims.sort_by_key(|&pid| self.index_of(pid).ok());
+        (cost, victims)
+    }
+
+    /// Whether sacrificing `victims` leaves the state safe.
+    fn safe_without(&self, recovery: Recovery, victims: &[usize]) -> bool {
+        let mut trial = self.clone();
+        trial.sacrifice(recovery, victims);
+        trial.is_safe_state_fast().is_some()
+    }
+
+    fn sacrifice(&mut self, recovery: Recovery, victims: &[usize]) {
+        for &pid in victims {
+            let index = self
+                .index_of(pid)
+                .expect("victims are taken from the process list");
+            let process = &mut self.processes[index];
+            let unfinishable = process
+                .max_need
+                .iter()
+                .zip(&self.resources)
+                .any(|(max, total)| max > total);
+            if recovery == Recovery::Terminate || unfinishable {
+                self.drop_pending(pid);
+                let process = self.processes.remove(index);
+                for (available, units) in self.available.iter_mut().zip(process.allocation) {
+                    *available += units;
+                }
+            } else {
+                for (k, units) in process.allocation.iter_mut().enumerate() {
+                    self.available[k] += *units;
+                    *units = 0;
+                }
+                process.need.clone_from(&process.max_need);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::state;
+
+    /// Nothing is available. Terminating P0 frees enough for the others,
+    /// and so does terminating both P1 and P2, which hold less.
+    fn stuck() -> BankersAlgorithm {
+        state(&[6], &[(&[4], &[6]), (&[1], &[4]), (&[1], &[4])])
+    }
+
+    /// Checks that carrying out the plan gives what it promised.
+    fn assert_applies(banker: &BankersAlgorithm, recovery: Recovery, cost: &RecoveryCost) {
+        let plan = banker.plan_recovery(recovery, cost);
+        let mut recovered = banker.clone();
+        assert_eq!(recovered.recover(recovery, cost), plan);
+
+        let reclaimed: Vec<u64> = recovered
+            .available()
+            .iter()
+            .zip(banker.available())
+            .map(|(after, before)| after - before)
+            .collect();
+        assert_eq!(reclaimed, plan.reclaimed);
+        assert_eq!(recovered.is_safe_state(), Some(plan.safe_sequence));
+    }
+
+    #[test]
+    fn safe_state_needs_no_victims() {
+        let banker = state(&[6], &[(&[4], &[6]), (&[1], &[2])]);
+        let plan = banker.plan_recovery(Recovery::Terminate, &RecoveryCost::FewestProcesses);
+        assert!(plan.victims.is_empty());
+        assert_eq!(plan.reclaimed, vec![0]);
+        assert_eq!(plan.safe_sequence, vec![1, 0]);
+    }
+
+    #[test]
+    fn fewest_processes_terminates_one() {
+        let banker = stuck();
+        let plan = banker.plan_recovery(Recovery::Terminate, &RecoveryCost::FewestProcesses);
+        assert_eq!(plan.victims, vec![0]);
+        assert_eq!(plan.cost, 1);
+        assert_eq!(plan.reclaimed, vec![4]);
+        assert_applies(&banker, Recovery::Terminate, &RecoveryCost::FewestProcesses);
+    }
+
+    #[test]
+    fn least_held_terminates_the_small_holders() {
+        let banker = stuck();
+        let plan = banker.plan_recovery(Recovery::Terminate, &RecoveryCost::LeastHeld);
+        assert_eq!(plan.victims, vec![1, 2]);
+        assert_eq!(plan.cost, 2);
+        assert_eq!(plan.reclaimed, vec![2]);
+        assert_applies(&banker, Recovery::Terminate, &RecoveryCost::LeastHeld);
+    }
+
+    #[test]
+    fn priority_picks_the_cheapest_victims() {
+        let banker = stuck();
+        let cost = RecoveryCost::Priority(HashMap::from([(0, 10), (1, 1), (2, 1)]));
+        let plan = banker.plan_recovery(Recovery::Terminate, &cost);
+        assert_eq!(plan.victims, vec![1, 2]);
+        assert_eq!(plan.cost, 2);
+
+        let cost = RecoveryCost::Priority(HashMap::from([(1, 1), (2, 1)]));
+        let plan = banker.plan_recovery(Recovery::Terminate, &cost);
+        assert_eq!(plan.victims, vec![0]);
+        assert_eq!(plan.cost, 0);
+        assert_applies(&banker, Recovery::Terminate, &cost);
+    }
+
+    #[test]
+    fn preempted_victims_stay_with_their_full_claim() {
+        let mut banker = stuck();
+        let plan = banker.recover(Recovery::Preempt, &RecoveryCost::FewestProcesses);
+        assert_eq!(plan.victims, vec![0]);
+        assert_eq!(plan.reclaimed, vec![4]);
+        assert_eq!(plan.safe_sequence, vec![1, 2, 0]);
+
+        let victim = banker.process(0).unwrap();
+        assert_eq!(victim.allocation(), [0]);
+        assert_eq!(victim.need(), [6]);
+        assert_applies(&stuck(), Recovery::Preempt, &RecoveryCost::LeastHeld);
+    }
+
+    #[test]
+    fn preempting_a_claim_beyond_the_totals_terminates_it() {
+        let mut banker = state(&[4], &[(&[1], &[4]), (&[2], &[3])]);
+        banker.resources[0] = 3;
+        banker.available[0] = 0;
+        let plan = banker.plan_recovery(Recovery::Preempt, &RecoveryCost::FewestProcesses);
+        assert_eq!(plan.victims, vec![0]);
+        assert_eq!(plan.reclaimed, vec![1]);
+        assert_eq!(plan.safe_sequence, vec![1]);
+        assert_applies(&banker, Recovery::Preempt, &RecoveryCost::LeastHeld);
+
+        let mut banker = state(&[4], &[(&[1], &[4]), (&[2], &[3])]);
+        banker.remove_capacity(0, 1, true).unwrap();
+        let plan = banker.recover(Recovery::Preempt, &RecoveryCost::FewestProcesses);
+        assert_eq!(banker.is_safe_state(), Some(plan.safe_sequence));
+    }
+
+    #[test]
+    fn terminated_victims_leave_with_their_queued_requests() {
+        let mut banker = stuck();
+        banker.request_or_queue(0, &[1], 0).unwrap();
+        banker.request_or_queue(1, &[1], 0).unwrap();
+        banker.recover(Recovery::Terminate, &RecoveryCost::FewestProcesses);
+
+        assert!(banker.process(0).is_none());
+        let pids: Vec<usize> = banker.pending().iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1]);
+    }
+
+    #[test]
+    fn many_blocked_processes_get_a_greedy_plan() {
+        // Each process needs 10 more units, so 10 of them have to go.
+        let rows: Vec<(&[u64], &[u64])> = vec![(&[1], &[11]); 20];
+        let banker = state(&[20], &rows);
+
+        let plan = banker.plan_recovery(Recovery::Terminate, &RecoveryCost::FewestProcesses);
+        assert_eq!(plan.victims, (0..10).collect::<Vec<usize>>());
+        assert_eq!(plan.cost, 10);
+        assert_applies(&banker, Recovery::Terminate, &RecoveryCost::FewestProcesses);
+    }
+}
@@ -0,0 +1,345 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{BankersAlgorithm, BankersError, Wakeup};
+
+/// A request that was granted, with the safe sequence that justified it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    pub pid: usize,
+    pub amount: Vec<u64>,
+    pub safe_sequence: Vec<usize>,
+}
+
+/// Units handed back by a process through `release` or `finish`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub pid: usize,
+    pub amount: Vec<u64>,
+    /// Queued requests that were granted, or finally refused, once the
+    /// units came back.
+    pub woken: Vec<Wakeup>,
+}
+
+/// Why a request or other change was refused. The state is left untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denial {
+    Invalid(BankersError),
+    ExceedsNeed {
+        pid: usize,
+        resource: usize,
+        requested: u64,
+        need: u64,
+    },
+    Unavailable {
+        pid: usize,
+        resource: usize,
+        requested: u64,
+        available: u64,
+    },
+    Unsafe {
+        pid: usize,
+    },
+    /// Admitting the process or raising its claim would leave the state unsafe.
+    ClaimUnsafe {
+        pid: usize,
+    },
+    /// Waiting for the request to become safe to grant took too long.
+    TimedOut {
+        pid: usize,
+    },
+    /// Removing capacity would leave these processes unable to finish.
+    Endangered {
+        resource: usize,
+        endangered: Vec<usize>,
+    },
+}
+
+impl From<BankersError> for Denial {
+    fn from(error: BankersError) -> Denial {
+        Denial::Invalid(error)
+    }
+}
+
+impl fmt::Display for Denial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Denial::Invalid(error) => error.fmt(f),
+            Denial::ExceedsNeed {
+                pid,
+                resource,
+                requested,
+                need,
+            } => write!(
+                f,
+                "Process {}: Request ({}) exceeds remaining need ({}) for resource {}.",
+                pid, requested, need, resource
+            ),
+            Denial::Unavailable {
+                pid,
+                resource,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Process {}: Request ({}) exceeds available resources ({}) for resource {}.",
+                pid, requested, available, resource
+            ),
+            Denial::Unsafe { pid } => write!(
+                f,
+                "Process {}: Granting the request would leave the system in an unsafe state.",
+                pid
+            ),
+            Denial::ClaimUnsafe { pid } => write!(
+                f,
+                "Process {}: Its maximum claim would leave the system in an unsafe state.",
+                pid
+            ),
+            Denial::TimedOut { pid } => write!(
+                f,
+                "Process {}: Timed out waiting for the request to become safe to grant.",
+                pid
+            ),
+            Denial::Endangered {
+                resource,
+                endangered,
+            } => {
+                let endangered: Vec<String> =
+                    endangered.iter().map(|pid| format!("P{}", pid)).collect();
+                write!(
+                    f,
+                    "Resource {}: Removing capacity would leave the system in an unsafe state, endangering {}.",
+                    resource,
+                    endangered.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl Error for Denial {}
+
+impl BankersAlgorithm {
+    /// Grants `amount` to process `pid` if doing so keeps the system safe.
+    ///
+    /// The request must not exceed the process's remaining need or the
+    /// available units. The allocation is made tentatively and rolled back
+    /// if the resulting state is unsafe.
+    pub fn request(&mut self, pid: usize, amount: &[u64]) -> Result<Grant, Denial> {
+        let index = self.index_of(pid)?;
+        self.check_len(pid, amount)?;
+
+        let process = &self.processes[index];
+        for (k, &requested) in amount.iter().enumerate() {
+            if requested > process.need[k] {
+                return Err(Denial::ExceedsNeed {
+                    pid,
+                    resource: k,
+                    requested,
+                    need: process.need[k],
+                });
+            }
+        }
+        for (k, &requested) in amount.iter().enumerate() {
+            if requested > self.available[k] {
+                return Err(Denial::Unavailable {
+                    pid,
+                    resource: k,
+                    requested,
+                    available: self.available[k],
+                });
+            }
+        }
+
+        self.allocate(index, amount);
+        match self.is_safe_state_fast() {
+            Some(safe_sequence) => Ok(Grant {
+                pid,
+                amount: amount.to_vec(),
+                safe_sequence,
+            }),
+            None => {
+                self.deallocate(index, amount);
+                Err(Denial::Unsafe { pid })
+            }
+        }
+    }
+
+    /// Returns `amount` from process `pid` to the available pool.
+    pub fn release(&mut self, pid: usize, amount: &[u64]) -> Result<Release, BankersError> {
+        let index = self.index_of(pid)?;
+        self.check_len(pid, amount)?;
+
+        let process = &self.processes[index];
+        for (k, &released) in amount.iter().enumerate() {
+            if released > process.allocation[k] {
+                return Err(BankersError::ReleaseExceedsAllocation {
+                    pid,
+                    resource: k,
+                    released,
+                    allocated: process.allocation[k],
+                });
+            }
+        }
+
+        self.deallocate(index, amount);
+        Ok(Release {
+            pid,
+            amount: amount.to_vec(),
+            woken: self.retry_pending(),
+        })
+    }
+
+    /// Terminates process `pid`, releasing everything it holds and removing
+    /// it along with its queued requests.
+    pub fn finish(&mut self, pid: usize) -> Result<Release, BankersError> {
+        let index = self.index_of(pid)?;
+        let process = self.processes.remove(index);
+        for (available, &units) in self.available.iter_mut().zip(&process.allocation) {
+            *available += units;
+        }
+        self.drop_pending(pid);
+        Ok(Release {
+            pid,
+            amount: process.allocation,
+            woken: self.retry_pending(),
+        })
+    }
+
+    fn allocate(&mut self, index: usize, amount: &[u64]) {
+        let process = &mut self.processes[index];
+        for (k, &units) in amount.iter().enumerate() {
+            self.available[k] -= units;
+            process.allocation[k] += units;
+            process.need[k] -= units;
+        }
+    }
+
+    fn deallocate(&mut self, index: usize, amount: &[u64]) {
+        let process = &mut self.processes[index];
+        for (k, &units) in amount.iter().enumerate() {
+            self.available[k] += units;
+            process.allocation[k] -= units;
+            process.need[k] += units;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::textbook;
+
+    #[test]
+    fn grants_a_safe_request() {
+        let mut banker = textbook();
+        let grant = banker.request(1, &[1, 0, 2]).unwrap();
+        assert_eq!(grant.amount, vec![1, 0, 2]);
+        assert_eq!(grant.safe_sequence, vec![1, 3, 4, 0, 2]);
+        assert_eq!(banker.available(), [2, 3, 0]);
+
+        let process = banker.process(1).unwrap();
+        assert_eq!(process.allocation(), [3, 0, 2]);
+        assert_eq!(process.need(), [0, 2, 0]);
+    }
+
+    #[test]
+    fn unsafe_request_leaves_the_state_untouched() {
+        let mut banker = textbook();
+        banker.request(1, &[1, 0, 2]).unwrap();
+        let before = banker.clone();
+
+        assert_eq!(
+            banker.request(0, &[0, 2, 0]),
+            Err(Denial::Unsafe { pid: 0 })
+        );
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn request_beyond_need_names_the_resource() {
+        let mut banker = textbook();
+        assert_eq!(
+            banker.request(1, &[0, 0, 3]),
+            Err(Denial::ExceedsNeed {
+                pid: 1,
+                resource: 2,
+                requested: 3,
+                need: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn request_beyond_available_names_the_resource() {
+        let mut banker = textbook();
+        assert_eq!(
+            banker.request(0, &[0, 0, 3]),
+            Err(Denial::Unavailable {
+                pid: 0,
+                resource: 2,
+                requested: 3,
+                available: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn request_checks_process_and_length() {
+        let mut banker = textbook();
+        assert_eq!(
+            banker.request(7, &[0, 0, 1]),
+            Err(Denial::Invalid(BankersError::UnknownProcess(7)))
+        );
+        assert_eq!(
+            banker.request(1, &[0, 1]),
+            Err(Denial::Invalid(BankersError::ResourceCountMismatch {
+                pid: 1,
+                expected: 3,
+                found: 2,
+            }))
+        );
+    }
+
+    #[test]
+    fn release_returns_units() {
+        let mut banker = textbook();
+        let release = banker.release(2, &[1, 0, 2]).unwrap();
+        assert_eq!(release.amount, vec![1, 0, 2]);
+        assert!(release.woken.is_empty());
+        assert_eq!(banker.available(), [4, 3, 4]);
+
+        let process = banker.process(2).unwrap();
+        assert_eq!(process.allocation(), [2, 0, 0]);
+        assert_eq!(process.need(), [7, 0, 2]);
+    }
+
+    #[test]
+    fn release_beyond_allocation_is_rejected() {
+        let mut banker = textbook();
+        let before = banker.clone();
+        assert_eq!(
+            banker.release(2, &[0, 0, 3]),
+            Err(BankersError::ReleaseExceedsAllocation {
+                pid: 2,
+                resource: 2,
+                released: 3,
+                allocated: 2,
+            })
+        );
+        assert_eq!(banker, before);
+    }
+
+    #[test]
+    fn finish_returns_everything_and_removes_the_process() {
+        let mut banker = textbook();
+        let release = banker.finish(2).unwrap();
+        assert_eq!(release.pid, 2);
+        assert_eq!(release.amount, vec![3, 0, 2]);
+        assert_eq!(banker.available(), [6, 3, 4]);
+        assert!(banker.process(2).is_none());
+        assert_eq!(banker.processes().len(), 4);
+
+        assert_eq!(banker.finish(2), Err(BankersError::UnknownProcess(2)));
+    }
+}
@@ -0,0 +1,312 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::BankersAlgorithm;
+
+/// Outcome of the safety or deadlock detection algorithm, including why it
+/// got stuck if it did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SafetyReport {
+    /// Processes that could run to completion, in the order they finished.
+    pub finished: Vec<usize>,
+    /// Processes that could not finish with the units freed up by the others.
+    /// For deadlock detection these are the deadlocked processes.
+    pub blocked: Vec<BlockedProcess>,
+    /// The `work` vector once no further process could finish.
+    pub work: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockedProcess {
+    pub pid: usize,
+    pub shortfalls: Vec<Shortfall>,
+}
+
+/// A resource where a blocked process needs, or for detection requests,
+/// more than `work` offers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortfall {
+    pub resource: usize,
+    pub need: u64,
+    pub work: u64,
+}
+
+/// One candidate examined by the safety algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceStep {
+    /// 1-based pass over the process list.
+    pub pass: usize,
+    pub pid: usize,
+    pub need: Vec<u64>,
+    /// `work` before the candidate was considered.
+    pub work: Vec<u64>,
+    /// Whether `need <= work` held, so the process was allowed to finish.
+    pub chosen: bool,
+    /// `work` after the step, including the released allocation if chosen.
+    pub work_after: Vec<u64>,
+}
+
+impl Shortfall {
+    pub fn missing(&self) -> u64 {
+        self.need - self.work
+    }
+}
+
+impl Serialize for Shortfall {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Shortfall", 4)?;
+        state.serialize_field("resource", &self.resource)?;
+        state.serialize_field("need", &self.need)?;
+        state.serialize_field("work", &self.work)?;
+        state.serialize_field("missing", &self.missing())?;
+        state.end()
+    }
+}
+
+impl SafetyReport {
+    pub fn is_safe(&self) -> bool {
+        self.blocked.is_empty()
+    }
+
+    /// The safe sequence, if every process finished.
+    pub fn safe_sequence(&self) -> Option<&[usize]> {
+        self.is_safe().then_some(&self.finished[..])
+    }
+
+    pub fn blocked_pids(&self) -> Vec<usize> {
+        self.blocked.iter().map(|blocked| blocked.pid).collect()
+    }
+}
+
+impl BankersAlgorithm {
+    /// Returns a safe sequence of process ids, or `None` if the state is unsafe.
+    pub fn is_safe_state(&self) -> Option<Vec<usize>> {
+        let report = self.check_safety();
+        if report.is_safe() {
+            Some(report.finished)
+        } else {
+            None
+        }
+    }
+
+    /// Runs the safety algorithm and reports the blocked processes and their
+    /// shortfalls when the state is unsafe.
+    pub fn check_safety(&self) -> SafetyReport {
+        self.run_safety(&self.needs(), None)
+    }
+
+    /// Like [`check_safety`](Self::check_safety), also recording every
+    /// candidate examined along the way.
+    pub fn trace_safety(&self) -> (SafetyReport, Vec<TraceStep>) {
+        let mut trace = Vec::new();
+        let report = self.run_safety(&self.needs(), Some(&mut trace));
+        (report, trace)
+    }
+
+    fn needs(&self) -> Vec<&[u64]> {
+        self.processes.iter().map(|p| &p.need[..]).collect()
+    }
+
+    /// Lets every process whose demand fits in `work` finish and hand back
+    /// its allocation, until none can. `demands` lines up with `processes`.
+    pub(crate) fn run_safety(
+        &self,
+        demands: &[&[u64]],
+        mut trace: Option<&mut Vec<TraceStep>>,
+    ) -> SafetyReport {
+        let num_processes = self.processes.len();
+        let mut work: Vec<u64> = self.available.clone();
+        let mut finish: Vec<bool> = vec![false; num_processes];
+        let mut safe_sequence: Vec<usize> = Vec::with_capacity(num_processes);
+
+        let mut pass = 0;
+        loop {
+            pass += 1;
+            let mut found_process_this_pass = false;
+            for (i, (process, &demand)) in self.processes.iter().zip(demands).enumerate() {
+                if !finish[i] {
+                    let work_before = trace.is_some().then(|| work.clone());
+                    let can_allocate = demand.iter().zip(&work).all(|(&n, &w)| n <= w);
+
+                    if can_allocate {
+                        for (w, &a) in work.iter_mut().zip(&process.allocation) {
+                            *w += a;
+                        }
+                        finish[i] = true;
+                        safe_sequence.push(process.id);
+                        found_process_this_pass = true;
+                    }
+
+                    if let (Some(trace), Some(work_before)) = (trace.as_mut(), work_before) {
+                        trace.push(TraceStep {
+                            pass,
+                            pid: process.id,
+                            need: demand.to_vec(),
+                            work: work_before,
+                            chosen: can_allocate,
+                            work_after: work.clone(),
+                        });
+                    }
+                }
+            }
+
+            if !found_process_this_pass {
+                break;
+            }
+        }
+
+        let blocked = self
+            .processes
+            .iter()
+            .zip(demands)
+            .zip(&finish)
+            .filter(|&(_, &finished)| !finished)
+            .map(|((process, demand), _)| BlockedProcess {
+                pid: process.id,
+                shortfalls: demand
+                    .iter()
+                    .zip(&work)
+                    .enumerate()
+                    .filter(|&(_, (&need, &work))| need > work)
+                    .map(|(resource, (&need, &work))| Shortfall {
+                        resource,
+                        need,
+                        work,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        SafetyReport {
+            finished: safe_sequence,
+            blocked,
+            work,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{state, textbook};
+
+    /// P1 can finish but hands back nothing. P0 then lacks units of R1, and
+    /// P2 lacks units of both resource types.
+    fn stuck() -> BankersAlgorithm {
+        state(
+            &[6, 4],
+            &[(&[2, 1], &[2, 4]), (&[0, 0], &[1, 1]), (&[2, 2], &[6, 4])],
+        )
+    }
+
+    #[test]
+    fn safe_state_reports_the_sequence() {
+        let report = textbook().check_safety();
+        assert!(report.is_safe());
+        assert_eq!(report.safe_sequence(), Some(&[1, 3, 4, 0, 2][..]));
+        assert!(report.blocked.is_empty());
+        assert_eq!(report.work, vec![10, 5, 7]);
+    }
+
+    #[test]
+    fn unsafe_state_reports_the_shortfalls() {
+        let report = stuck().check_safety();
+        assert!(!report.is_safe());
+        assert_eq!(report.safe_sequence(), None);
+        assert_eq!(report.finished, vec![1]);
+        assert_eq!(report.work, vec![2, 1]);
+        assert_eq!(report.blocked_pids(), vec![0, 2]);
+        assert_eq!(
+            report.blocked,
+            vec![
+                BlockedProcess {
+                    pid: 0,
+                    shortfalls: vec![Shortfall {
+                        resource: 1,
+                        need: 3,
+                        work: 1,
+                    }],
+                },
+                BlockedProcess {
+                    pid: 2,
+                    shortfalls: vec![
+                        Shortfall {
+                            resource: 0,
+                            need: 4,
+                            work: 2,
+                        },
+                        Shortfall {
+                            resource: 1,
+                            need: 2,
+                            work: 1,
+                        },
+                    ],
+                },
+            ]
+        );
+
+        let missing: Vec<u64> = report.blocked[1]
+            .shortfalls
+            .iter()
+            .map(Shortfall::missing)
+            .collect();
+        assert_eq!(missing, vec![2, 1]);
+        assert_eq!(stuck().is_safe_state(), None);
+    }
+
+    #[test]
+    fn shortfall_serializes_what_is_missing() {
+        let shortfall = Shortfall {
+            resource: 0,
+            need: 4,
+            work: 2,
+        };
+        assert_eq!(
+            serde_json::to_value(&shortfall).unwrap(),
+            serde_json::json!({ "resource": 0, "need": 4, "work": 2, "missing": 2 })
+        );
+    }
+
+    #[test]
+    fn trace_records_every_candidate_by_pass() {
+        let (report, trace) = textbook().trace_safety();
+        assert_eq!(report, textbook().check_safety());
+
+        let step =
+            |pass, pid, need: [u64; 3], work: [u64; 3], chosen, work_after: [u64; 3]| TraceStep {
+                pass,
+                pid,
+                need: need.to_vec(),
+                work: work.to_vec(),
+                chosen,
+                work_after: work_after.to_vec(),
+            };
+        assert_eq!(
+            trace,
+            vec![
+                step(1, 0, [7, 4, 3], [3, 3, 2], false, [3, 3, 2]),
+                step(1, 1, [1, 2, 2], [3, 3, 2], true, [5, 3, 2]),
+                step(1, 2, [6, 0, 0], [5, 3, 2], false, [5, 3, 2]),
+                step(1, 3, [0, 1, 1], [5, 3, 2], true, [7, 4, 3]),
+                step(1, 4, [4, 3, 1], [7, 4, 3], true, [7, 4, 5]),
+                step(2, 0, [7, 4, 3], [7, 4, 5], true, [7, 5, 5]),
+                step(2, 2, [6, 0, 0], [7, 5, 5], true, [10, 5, 7]),
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_ends_with_a_pass_that_finishes_nobody() {
+        let (report, trace) = stuck().trace_safety();
+        assert_eq!(report, stuck().check_safety());
+
+        let last_pass: Vec<(usize, usize, bool)> = trace
+            .iter()
+            .filter(|step| step.pass == 2)
+            .map(|step| (step.pass, step.pid, step.chosen))
+            .collect();
+        assert_eq!(last_pass, vec![(2, 0, false), (2, 2, false)]);
+        assert_eq!(trace.len(), 5);
+        assert_eq!(trace[2].need, vec![4, 2]);
+    }
+}
@@ -0,0 +1,334 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BankersAlgorithm, BankersError, ParseError, Process};
+
+/// A system state as stored in a JSON, TOML or matrix text scenario file.
+///
+/// ```toml
+/// resources = [10, 5, 7]
+///
+/// [[processes]]
+/// allocation = [0, 1, 0]
+/// max = [7, 5, 3]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub resources: Vec<u64>,
+    pub processes: Vec<ScenarioProcess>,
+}
+
+/// One process entry. The id defaults to the entry's position in the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScenarioProcess {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub allocation: Vec<u64>,
+    pub max: Vec<u64>,
+    /// Units the process is waiting for right now, for deadlock detection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Vec<u64>>,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    Parse(ParseError),
+    UnsupportedFormat(String),
+    Invalid(BankersError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "Could not read scenario: {}", e),
+            LoadError::Json(e) => write!(f, "Invalid JSON scenario: {}", e),
+            LoadError::Toml(e) => write!(f, "Invalid TOML scenario: {}", e),
+            LoadError::Parse(e) => write!(f, "Invalid matrix scenario: {}", e),
+            LoadError::UnsupportedFormat(ext) => write!(
+                f,
+                "Unsupported scenario format '{}'. Expected .json, .toml or .txt.",
+                ext
+            ),
+            LoadError::Invalid(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Json(e) => Some(e),
+            LoadError::Toml(e) => Some(e),
+            LoadError::Parse(e) => Some(e),
+            LoadError::UnsupportedFormat(_) => None,
+            LoadError::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> LoadError {
+        LoadError::Io(error)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(error: ParseError) -> LoadError {
+        LoadError::Parse(error)
+    }
+}
+
+impl From<BankersError> for LoadError {
+    fn from(error: BankersError) -> LoadError {
+        LoadError::Invalid(error)
+    }
+}
+
+impl Scenario {
+    pub fn from_json(input: &str) -> Result<Scenario, LoadError> {
+        serde_json::from_str(input).map_err(LoadError::Json)
+    }
+
+    pub fn from_toml(input: &str) -> Result<Scenario, LoadError> {
+        toml::from_str(input).map_err(LoadError::Toml)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("scenario serializes to JSON")
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("scenario serializes to TOML")
+    }
+
+    /// Reads a scenario file, picking the format from its extension.
+    pub fn load(path: &Path) -> Result<Scenario, LoadError> {
+        let extension = extension(path);
+        match extension.as_str() {
+            "json" => Scenario::from_json(&fs::read_to_string(path)?),
+            "toml" => Scenario::from_toml(&fs::read_to_string(path)?),
+            "txt" | "" => Scenario::from_matrix(&fs::read_to_string(path)?),
+            _ => Err(LoadError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Writes the scenario, picking the format from the file extension.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = match extension(path).as_str() {
+            "json" => self.to_json() + "\n",
+            "toml" => self.to_toml(),
+            "txt" | "" => self.to_matrix(),
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    LoadError::UnsupportedFormat(other.to_string()).to_string(),
+                ));
+            }
+        };
+        fs::write(path, contents)
+    }
+
+    /// The request row of every process for
+    /// [`BankersAlgorithm::detect_deadlock`], with nothing requested where
+    /// the file gives no request.
+    pub fn requests(&self) -> Vec<Vec<u64>> {
+        self.processes
+            .iter()
+            .map(|p| {
+                p.request
+                    .clone()
+                    .unwrap_or_else(|| vec![0; self.resources.len()])
+            })
+            .collect()
+    }
+
+    /// Validates the scenario with the same rules as [`BankersAlgorithm::from_parts`].
+    pub fn into_banker(self) -> Result<BankersAlgorithm, BankersError> {
+        let processes = self
+            .processes
+            .into_iter()
+            .enumerate()
+            .map(|(index, p)| Process::new(p.id.unwrap_or(index), p.allocation, p.max))
+            .collect::<Result<Vec<Process>, BankersError>>()?;
+
+        BankersAlgorithm::from_parts(self.resources, processes)
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+impl From<&BankersAlgorithm> for Scenario {
+    fn from(banker: &BankersAlgorithm) -> Scenario {
+        Scenario {
+            resources: banker.resources.clone(),
+            processes: banker
+                .processes
+                .iter()
+                .enumerate()
+                .map(|(index, p)| ScenarioProcess {
+                    id: (p.id != index).then_some(p.id),
+                    allocation: p.allocation.clone(),
+                    max: p.max_need.clone(),
+                    request: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl BankersAlgorithm {
+    /// Loads and validates a JSON, TOML or matrix text scenario file.
+    pub fn load(path: &Path) -> Result<BankersAlgorithm, LoadError> {
+        Ok(Scenario::load(path)?.into_banker()?)
+    }
+
+    /// Saves the state as a JSON, TOML or matrix text scenario file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        Scenario::from(self).save(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::textbook;
+
+    const TOML: &str = r#"
+resources = [4, 2]
+
+[[processes]]
+allocation = [1, 0]
+max = [3, 1]
+
+[[processes]]
+id = 7
+allocation = [2, 1]
+max = [2, 2]
+request = [0, 1]
+"#;
+
+    /// A file in the temporary directory, removed when dropped.
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            TempFile(std::env::temp_dir().join(format!(
+                "bankers_algo_{}_{}",
+                std::process::id(),
+                name
+            )))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn toml_ids_default_to_their_position() {
+        let scenario = Scenario::from_toml(TOML).unwrap();
+        assert_eq!(scenario.processes[0].id, None);
+        assert_eq!(scenario.processes[1].request, Some(vec![0, 1]));
+        assert_eq!(scenario.requests(), vec![vec![0, 0], vec![0, 1]]);
+
+        let banker = scenario.into_banker().unwrap();
+        let ids: Vec<usize> = banker.processes().iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec![0, 7]);
+        assert_eq!(banker.available(), [1, 1]);
+        assert_eq!(banker.process(0).unwrap().need(), [2, 1]);
+    }
+
+    #[test]
+    fn json_matches_toml() {
+        let json = r#"{
+            "resources": [4, 2],
+            "processes": [
+                { "allocation": [1, 0], "max": [3, 1] },
+                { "id": 7, "allocation": [2, 1], "max": [2, 2], "request": [0, 1] }
+            ]
+        }"#;
+        assert_eq!(
+            Scenario::from_json(json).unwrap(),
+            Scenario::from_toml(TOML).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(matches!(
+            Scenario::from_json("{ \"resources\": [1] }"),
+            Err(LoadError::Json(_))
+        ));
+        assert!(matches!(
+            Scenario::from_toml("resources = 1"),
+            Err(LoadError::Toml(_))
+        ));
+        assert!(matches!(
+            Scenario::load(Path::new("state.yaml")),
+            Err(LoadError::UnsupportedFormat(ext)) if ext == "yaml"
+        ));
+    }
+
+    #[test]
+    fn into_banker_validates_the_state() {
+        let mut scenario = Scenario::from_toml(TOML).unwrap();
+        scenario.processes[0].max = vec![5, 1];
+        assert_eq!(
+            scenario.clone().into_banker(),
+            Err(BankersError::MaxExceedsTotal {
+                pid: 0,
+                resource: 0,
+                max: 5,
+                total: 4,
+            })
+        );
+
+        scenario.processes[0].max = vec![3, 1];
+        scenario.processes[0].id = Some(7);
+        assert_eq!(
+            scenario.into_banker(),
+            Err(BankersError::DuplicateProcess(7))
+        );
+    }
+
+    #[test]
+    fn saved_states_load_back_in_every_format() {
+        let mut banker = textbook();
+        banker.finish(2).unwrap();
+
+        for format in ["json", "toml", "txt"] {
+            let file = TempFile::new(&format!("round_trip.{}", format));
+            banker.save(&file.0).unwrap();
+            assert_eq!(
+                BankersAlgorithm::load(&file.0).unwrap(),
+                banker,
+                "{}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn saving_to_an_unknown_format_fails() {
+        let file = TempFile::new("state.yaml");
+        let error = textbook().save(&file.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!file.0.exists());
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::BankersAlgorithm;
+
+impl BankersAlgorithm {
+    /// Lists every safe sequence by backtracking over all orderings,
+    /// stopping after `limit` sequences if given.
+    pub fn all_safe_sequences(&self, limit: Option<usize>) -> Vec<Vec<usize>> {
+        let mut sequences = Vec::new();
+        if limit != Some(0) {
+            let mut finish = vec![false; self.processes.len()];
+            let mut sequence = Vec::with_capacity(self.processes.len());
+            self.collect_sequences(
+                &mut self.available.clone(),
+                &mut finish,
+                &mut sequence,
+                limit,
+                &mut sequences,
+            );
+        }
+        sequences
+    }
+
+    /// Counts the safe sequences without materializing them.
+    ///
+    /// Orderings that reach the same set of finished processes share the
+    /// same `work` vector, so sub-counts are memoized on that set. With a
+    /// `limit` the count saturates at that value.
+    pub fn count_safe_sequences(&self, limit: Option<u128>) -> u128 {
+        let limit = limit.unwrap_or(u128::MAX);
+        let mut finish = vec![false; self.processes.len()];
+        let mut memo = HashMap::new();
+        self.count_sequences(&mut self.available.clone(), &mut finish, limit, &mut memo)
+    }
+
+    fn can_finish(&self, index: usize, work: &[u64]) -> bool {
+        self.processes[index]
+            .need
+            .iter()
+            .zip(work)
+            .all(|(&n, &w)| n <= w)
+    }
+
+    fn collect_sequences(
+        &self,
+        work: &mut Vec<u64>,
+        finish: &mut Vec<bool>,
+        sequence: &mut Vec<usize>,
+        limit: Option<usize>,
+        sequences: &mut Vec<Vec<usize>>,
+    ) -> bool {
+        if sequence.len() == self.processes.len() {
+            sequences.push(sequence.clone());
+            return limit.is_some_and(|limit| sequences.len() >= limit);
+        }
+
+        for i in 0..self.processes.len() {
+            if finish[i] || !self.can_finish(i, work) {
+                continue;
+            }
+
+            let process = &self.processes[i];
+            for (w, &a) in work.iter_mut().zip(&process.allocation) {
+                *w += a;
+            }
+            finish[i] = true;
+            sequence.push(process.id);
+
+            let done = self.collect_sequences(work, finish, sequence, limit, sequences);
+
+            sequence.pop();
+            finish[i] = false;
+            for (w, &a) in work.iter_mut().zip(&process.allocation) {
+                *w -= a;
+            }
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn count_sequences(
+        &self,
+        work: &mut Vec<u64>,
+        finish: &mut Vec<bool>,
+        limit: u128,
+        memo: &mut HashMap<Vec<bool>, u128>,
+    ) -> u128 {
+        if finish.iter().all(|&f| f) {
+            return 1;
+        }
+        if let Some(&count) = memo.get(finish) {
+            return count;
+        }
+
+        let mut count: u128 = 0;
+        for i in 0..self.processes.len() {
+            if finish[i] || !self.can_finish(i, work) {
+                continue;
+            }
+
+            let process = &self.processes[i];
+            for (w, &a) in work.iter_mut().zip(&process.allocation) {
+                *w += a;
+            }
+            finish[i] = true;
+
+            let sub_count = self.count_sequences(work, finish, limit, memo);
+
+            finish[i] = false;
+            for (w, &a) in work.iter_mut().zip(&process.allocation) {
+                *w -= a;
+            }
+
+            count = count.saturating_add(sub_count).min(limit);
+            if count == limit {
+                break;
+            }
+        }
+
+        memo.insert(finish.clone(), count);
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{state, textbook};
+
+    /// Every ordering of the processes that lets each one finish in turn,
+    /// found by trying all permutations in lexicographic order.
+    fn brute_force(banker: &BankersAlgorithm) -> Vec<Vec<usize>> {
+        fn permute(
+            banker: &BankersAlgorithm,
+            order: &mut Vec<usize>,
+            left: &mut Vec<usize>,
+            found: &mut Vec<Vec<usize>>,
+        ) {
+            if left.is_empty() {
+                let mut work = banker.available().to_vec();
+                for &index in order.iter() {
+                    let process = &banker.processes()[index];
+                    if process
+                        .need()
+                        .iter()
+                        .zip(&work)
+                        .any(|(need, work)| need > work)
+                    {
+                        return;
+                    }
+                    for (work, units) in work.iter_mut().zip(process.allocation()) {
+                        *work += units;
+                    }
+                }
+                found.push(order.iter().map(|&i| banker.processes()[i].id()).collect());
+                return;
+            }
+            for position in 0..left.len() {
+                let index = left.remove(position);
+                order.push(index);
+                permute(banker, order, left, found);
+                order.pop();
+                left.insert(position, index);
+            }
+        }
+
+        let mut found = Vec::new();
+        let mut left: Vec<usize> = (0..banker.processes().len()).collect();
+        permute(banker, &mut Vec::new(), &mut left, &mut found);
+        found
+    }
+
+    /// `num_processes` processes that hold one unit each and need no more,
+    /// so every ordering is safe.
+    fn idle(num_processes: usize) -> BankersAlgorithm {
+        let rows: Vec<(&[u64], &[u64])> = vec![(&[1], &[1]); num_processes];
+        state(&[num_processes as u64], &rows)
+    }
+
+    fn fixtures() -> Vec<BankersAlgorithm> {
+        let mut granted = textbook();
+        granted.request(1, &[1, 0, 2]).unwrap();
+        vec![
+            textbook(),
+            granted,
+            idle(6),
+            state(
+                &[7, 3],
+                &[
+                    (&[1, 0], &[3, 2]),
+                    (&[2, 1], &[4, 1]),
+                    (&[0, 1], &[2, 3]),
+                    (&[1, 0], &[1, 1]),
+                    (&[2, 0], &[5, 2]),
+                    (&[0, 0], &[7, 3]),
+                    (&[0, 1], &[1, 1]),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn lists_every_safe_sequence() {
+        let banker = textbook();
+        let sequences = banker.all_safe_sequences(None);
+        assert_eq!(sequences, brute_force(&banker));
+        assert_eq!(sequences.len(), 16);
+        assert_eq!(sequences[0], vec![1, 3, 0, 2, 4]);
+        assert!(sequences.contains(&banker.is_safe_state().unwrap()));
+
+        for banker in fixtures() {
+            assert_eq!(banker.all_safe_sequences(None), brute_force(&banker));
+        }
+    }
+
+    #[test]
+    fn limit_cuts_the_list_short() {
+        let banker = textbook();
+        let sequences = banker.all_safe_sequences(None);
+        assert_eq!(banker.all_safe_sequences(Some(3)), sequences[..3]);
+        assert_eq!(banker.all_safe_sequences(Some(100)), sequences);
+        assert!(banker.all_safe_sequences(Some(0)).is_empty());
+    }
+
+    #[test]
+    fn unsafe_state_has_no_sequences() {
+        let banker = state(&[2], &[(&[1], &[2]), (&[1], &[2])]);
+        assert!(banker.all_safe_sequences(None).is_empty());
+        assert_eq!(banker.count_safe_sequences(None), 0);
+    }
+
+    #[test]
+    fn count_matches_the_list() {
+        for banker in fixtures() {
+            let listed = brute_force(&banker).len() as u128;
+            assert_eq!(banker.count_safe_sequences(None), listed);
+        }
+        assert_eq!(fixtures()[2].count_safe_sequences(None), 720);
+    }
+
+    #[test]
+    fn count_saturates_at_the_limit() {
+        let banker = textbook();
+        assert_eq!(banker.count_safe_sequences(Some(10)), 10);
+        assert_eq!(banker.count_safe_sequences(Some(16)), 16);
+        assert_eq!(banker.count_safe_sequences(Some(1_000)), 16);
+        assert_eq!(banker.count_safe_sequences(Some(0)), 0);
+
+        let idle = idle(16);
+        assert_eq!(idle.count_safe_sequences(Some(1_000_000)), 1_000_000);
+        assert_eq!(idle.count_safe_sequences(None), (1..=16u128).product());
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{BankersAlgorithm, BankersError, Denial, Grant, Queued};
+
+/// A [`BankersAlgorithm`] that threads can share to guard real resources,
+/// such as connection pools or worker slots.
+///
+/// Threads block in [`acquire`](Self::acquire), and async tasks await
+/// [`acquire_async`](Self::acquire_async), until their request can be
+/// granted safely, and get a [`Permit`] that hands the units back when
+/// dropped. Waiting requests go through the banker's queue, so they are
+/// served in the order of its [`QueuePolicy`](crate::QueuePolicy).
+#[derive(Debug)]
+pub struct SharedBanker {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    banker: BankersAlgorithm,
+    /// Outcomes of queued requests that their waiters have not picked up yet.
+    settled: HashMap<u64, Result<Grant, Denial>>,
+    /// Tasks waiting in [`Acquire`] futures, by ticket.
+    wakers: HashMap<u64, Waker>,
+    /// Permits that have not been dropped yet.
+    permits: usize,
+}
+
+/// Units granted to a process, released when the permit is dropped.
+#[derive(Debug)]
+#[must_use = "the units are released as soon as the permit is dropped"]
+pub struct Permit<'a> {
+    shared: &'a SharedBanker,
+    pid: usize,
+    amount: Vec<u64>,
+}
+
+impl SharedBanker {
+    pub fn new(mut banker: BankersAlgorithm) -> SharedBanker {
+        banker.woken = Some(Vec::new());
+        SharedBanker {
+            state: Mutex::new(State {
+                banker,
+                settled: HashMap::new(),
+                wakers: HashMap::new(),
+                permits: 0,
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Grants `amount` to process `pid`, waiting as long as it takes for
+    /// the grant to be safe.
+    ///
+    /// Requests that can never be granted, e.g. because they exceed the
+    /// process's need, fail right away.
+    pub fn acquire(&self, pid: usize, amount: &[u64]) -> Result<Permit<'_>, Denial> {
+        self.acquire_until(pid, amount, None)
+    }
+
+    /// Grants `amount` to process `pid` once that is safe, without blocking
+    /// the thread. Works with any executor.
+    ///
+    /// Dropping the future before it completes takes the request out of
+    /// the queue, and gives back the units if they were just granted.
+    pub fn acquire_async(&self, pid: usize, amount: &[u64]) -> Acquire<'_> {
+        Acquire {
+            shared: self,
+            pid,
+            amount: amount.to_vec(),
+            ticket: None,
+        }
+    }
+
+    /// Grants `amount` to process `pid` only if that is safe right now.
+    pub fn try_acquire(&self, pid: usize, amount: &[u64]) -> Result<Permit<'_>, Denial> {
+        let mut state = self.lock();
+        let grant = state.banker.request(pid, amount)?;
+        Ok(self.permit(&mut state, grant))
+    }
+
+    /// Like [`acquire`](Self::acquire), giving up with [`Denial::TimedOut`]
+    /// after `timeout`.
+    pub fn acquire_timeout(
+        &self,
+        pid: usize,
+        amount: &[u64],
+        timeout: Duration,
+    ) -> Result<Permit<'_>, Denial> {
+        self.acquire_until(pid, amount, Some(Instant::now() + timeout))
+    }
+
+    /// Runs `change` on the state, e.g. to admit or finish a process, then
+    /// retries the waiting requests. Requests that `change` settles itself,
+    /// as [`finish`](BankersAlgorithm::finish) does, reach their waiters too.
+    ///
+    /// While permits hold units, adding or retiring a resource type fails
+    /// with [`BankersError::ResourceTypesLocked`].
+    ///
+    /// If `change` panics, whatever it changed so far stays in place and
+    /// other threads carry on with that state, so it should leave the banker
+    /// consistent at every point it can panic.
+    pub fn update<R>(&self, change: impl FnOnce(&mut BankersAlgorithm) -> R) -> R {
+        let mut state = self.lock();
+        state.banker.layout_locked =
+            state.permits > 0 || state.settled.values().any(|outcome| outcome.is_ok());
+        let result = change(&mut state.banker);
+        state.banker.layout_locked = false;
+        // `change` may have replaced the banker with one that keeps no record.
+        state.banker.woken.get_or_insert_with(Vec::new);
+        state.banker.retry_pending();
+        self.settle(&mut state);
+        result
+    }
+
+    /// A copy of the current state.
+    pub fn snapshot(&self) -> BankersAlgorithm {
+        let mut banker = self.lock().banker.clone();
+        banker.woken = None;
+        banker.layout_locked = false;
+        banker
+    }
+
+    fn acquire_until(
+        &self,
+        pid: usize,
+        amount: &[u64],
+        deadline: Option<Instant>,
+    ) -> Result<Permit<'_>, Denial> {
+        let mut state = self.lock();
+        let ticket = match state.banker.request_or_queue(pid, amount, 0)? {
+            Queued::Granted(grant) => return Ok(self.permit(&mut state, grant)),
+            Queued::Waiting(ticket) => ticket,
+        };
+
+        loop {
+            if let Some(outcome) = state.settled.remove(&ticket) {
+                return outcome.map(|grant| self.permit(&mut state, grant));
+            }
+            // Finishing the process drops its queued requests.
+            if !state.banker.pending().iter().any(|p| p.ticket == ticket) {
+                return Err(BankersError::UnknownProcess(pid).into());
+            }
+
+            state = match deadline {
+                None => self
+                    .changed
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        state.banker.cancel(ticket);
+                        return Err(Denial::TimedOut { pid });
+                    }
+                    self.changed
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+            };
+        }
+    }
+
+    /// Takes the amount from the grant, since a queued request may have
+    /// gained or lost resource types while it waited.
+    fn permit(&self, state: &mut State, grant: Grant) -> Permit<'_> {
+        state.permits += 1;
+        Permit {
+            shared: self,
+            pid: grant.pid,
+            amount: grant.amount,
+        }
+    }
+
+    /// Hands back units granted to `pid`. A process that has finished in
+    /// the meantime already returned them, so there is nothing to release.
+    fn give_back(&self, state: &mut State, pid: usize, amount: &[u64]) {
+        match state.banker.release(pid, amount) {
+            Ok(_) => self.settle(state),
+            Err(BankersError::UnknownProcess(_)) => {}
+            Err(error) if !thread::panicking() => {
+                panic!(
+                    "units granted to process {} cannot be released: {}",
+                    pid, error
+                )
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Hands the outcomes of retried requests to their waiters. Tasks whose
+    /// request left the queue with its process are woken as well.
+    fn settle(&self, state: &mut State) {
+        let woken = std::mem::take(state.banker.woken.get_or_insert_with(Vec::new));
+        for wakeup in woken {
+            if let Some(waker) = state.wakers.remove(&wakeup.ticket) {
+                waker.wake();
+            }
+            state.settled.insert(wakeup.ticket, wakeup.outcome);
+        }
+        let State { banker, wakers, .. } = state;
+        wakers.retain(|&ticket, waker| {
+            let waiting = banker.pending().iter().any(|p| p.ticket == ticket);
+            if !waiting {
+                waker.wake_by_ref();
+            }
+            waiting
+        });
+        self.changed.notify_all();
+    }
+
+    /// Poisoning is ignored: the banker's own calls check their input before
+    /// changing anything, so only a panic in [`update`](Self::update)'s
+    /// closure can leave a change half made.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Future returned by [`SharedBanker::acquire_async`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Acquire<'a> {
+    shared: &'a SharedBanker,
+    pid: usize,
+    amount: Vec<u64>,
+    /// Set once the request is waiting in the queue.
+    ticket: Option<u64>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Result<Permit<'a>, Denial>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let shared = this.shared;
+        let mut state = shared.lock();
+
+        let ticket = match this.ticket {
+            Some(ticket) => ticket,
+            None => match state.banker.request_or_queue(this.pid, &this.amount, 0) {
+                Ok(Queued::Granted(grant)) => {
+                    return Poll::Ready(Ok(shared.permit(&mut state, grant)));
+                }
+                Ok(Queued::Waiting(ticket)) => {
+                    this.ticket = Some(ticket);
+                    ticket
+                }
+                Err(denial) => return Poll::Ready(Err(denial)),
+            },
+        };
+
+        if let Some(outcome) = state.settled.remove(&ticket) {
+            this.ticket = None;
+            return Poll::Ready(outcome.map(|grant| shared.permit(&mut state, grant)));
+        }
+        if !state.banker.pending().iter().any(|p| p.ticket == ticket) {
+            this.ticket = None;
+            return Poll::Ready(Err(BankersError::UnknownProcess(this.pid).into()));
+        }
+        state.wakers.insert(ticket, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    /// Withdraws a request that is still waiting. One that was granted after
+    /// the last poll gives its units back.
+    fn drop(&mut self) {
+        let Some(ticket) = self.ticket else {
+            return;
+        };
+        let mut state = self.shared.lock();
+        state.wakers.remove(&ticket);
+        if state.banker.cancel(ticket).is_some() {
+            return;
+        }
+        if let Some(Ok(grant)) = state.settled.remove(&ticket) {
+            self.shared.give_back(&mut state, grant.pid, &grant.amount);
+        }
+    }
+}
+
+impl Permit<'_> {
+    pub fn pid(&self) -> usize {
+        self.pid
+    }
+
+    pub fn amount(&self) -> &[u64] {
+        &self.amount
+    }
+}
+
+impl Drop for Permit<'_> {
+    /// Releases the units. If the process has finished in the meantime they
+    /// were already returned, and there is nothing left to release.
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.permits -= 1;
+        self.shared.give_back(&mut state, self.pid, &self.amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+
+    use super::*;
+    use crate::testing::state;
+
+    /// Two processes that may each claim both units of a single resource.
+    fn pool() -> SharedBanker {
+        SharedBanker::new(state(&[2], &[(&[0], &[2]), (&[0], &[2])]))
+    }
+
+    fn wait_for_pending(shared: &SharedBanker, count: usize) {
+        while shared.snapshot().pending().len() != count {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn dropped_permit_wakes_a_blocked_acquirer() {
+        let shared = pool();
+        let held = shared.acquire(0, &[2]).unwrap();
+
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| {
+                let permit = shared.acquire(1, &[1]).unwrap();
+                assert_eq!(permit.amount(), [1]);
+                shared.snapshot().available().to_vec()
+            });
+            wait_for_pending(&shared, 1);
+            drop(held);
+            assert_eq!(waiter.join().unwrap(), vec![1]);
+        });
+
+        let state = shared.snapshot();
+        assert_eq!(state.available(), [2]);
+        assert!(state.pending().is_empty());
+    }
+
+    #[test]
+    fn try_acquire_does_not_wait() {
+        let shared = pool();
+        let _held = shared.try_acquire(0, &[2]).unwrap();
+        assert!(matches!(
+            shared.try_acquire(1, &[1]),
+            Err(Denial::Unavailable { pid: 1, .. })
+        ));
+        assert!(shared.snapshot().pending().is_empty());
+    }
+
+    #[test]
+    fn timed_out_acquire_leaves_the_queue() {
+        let shared = pool();
+        let _held = shared.acquire(0, &[2]).unwrap();
+
+        let outcome = shared.acquire_timeout(1, &[1], Duration::from_millis(20));
+        assert!(matches!(outcome, Err(Denial::TimedOut { pid: 1 })));
+
+        assert!(shared.snapshot().pending().is_empty());
+        let state = shared.lock();
+        assert!(state.settled.is_empty());
+        assert!(state.wakers.is_empty());
+    }
+
+    #[test]
+    fn finishing_inside_update_hands_over_the_units() {
+        let shared = pool();
+        let held = shared.acquire(0, &[2]).unwrap();
+
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| shared.acquire(1, &[2]).map(|permit| permit.pid()));
+            wait_for_pending(&shared, 1);
+            let release = shared.update(|banker| banker.finish(0)).unwrap();
+            assert_eq!(release.woken.len(), 1);
+            assert_eq!(waiter.join().unwrap(), Ok(1));
+        });
+        // P0 is gone, so its permit has nothing left to release.
+        drop(held);
+
+        let state = shared.snapshot();
+        assert_eq!(state.available(), [2]);
+        assert_eq!(state.process(1).unwrap().allocation(), [0]);
+        assert!(shared.lock().settled.is_empty());
+    }
+
+    #[test]
+    fn waiter_fails_when_its_process_finishes() {
+        let shared = pool();
+        let _held = shared.acquire(0, &[2]).unwrap();
+
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| shared.acquire(1, &[1]).map(|permit| permit.pid()));
+            wait_for_pending(&shared, 1);
+            shared.update(|banker| banker.finish(1)).unwrap();
+            assert_eq!(
+                waiter.join().unwrap(),
+                Err(Denial::Invalid(BankersError::UnknownProcess(1)))
+            );
+        });
+    }
+
+    #[test]
+    fn contended_acquires_all_get_through() {
+        let shared = SharedBanker::new(state(
+            &[4, 3],
+            &[
+                (&[0, 0], &[3, 2]),
+                (&[0, 0], &[2, 2]),
+                (&[0, 0], &[4, 1]),
+                (&[0, 0], &[1, 3]),
+            ],
+        ));
+
+        thread::scope(|scope| {
+            for pid in 0..4 {
+                let shared = &shared;
+                scope.spawn(move || {
+                    for round in 0..50 {
+                        let need = shared.snapshot().process(pid).unwrap().need().to_vec();
+                        let amount: Vec<u64> =
+                            need.iter().map(|&units| (units + round) % 2).collect();
+                        let _permit = shared.acquire(pid, &amount).unwrap();
+                        thread::yield_now();
+                    }
+                });
+            }
+        });
+
+        let state = shared.snapshot();
+        assert_eq!(state.available(), [4, 3]);
+        assert!(state.pending().is_empty());
+    }
+
+    /// Records whether the task was woken.
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Polls `future` once with a waker that sets `flag`.
+    fn poll_once<F: Future>(future: Pin<&mut F>, flag: &Arc<Flag>) -> Poll<F::Output> {
+        let waker = Waker::from(flag.clone());
+        future.poll(&mut Context::from_waker(&waker))
+    }
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn dropping_a_waiting_future_withdraws_the_request() {
+        let shared = pool();
+        let _held = shared.acquire(0, &[2]).unwrap();
+        let flag = Arc::new(Flag::default());
+
+        let mut acquire = Box::pin(shared.acquire_async(1, &[1]));
+        assert!(poll_once(acquire.as_mut(), &flag).is_pending());
+        assert_eq!(shared.snapshot().pending().len(), 1);
+
+        drop(acquire);
+        assert!(shared.snapshot().pending().is_empty());
+        assert!(shared.lock().wakers.is_empty());
+        assert!(!flag.0.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn dropping_a_granted_future_gives_the_units_back() {
+        let shared = pool();
+        let held = shared.acquire(0, &[2]).unwrap();
+        let flag = Arc::new(Flag::default());
+
+        let mut acquire = Box::pin(shared.acquire_async(1, &[1]));
+        assert!(poll_once(acquire.as_mut(), &flag).is_pending());
+        drop(held);
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert_eq!(shared.snapshot().process(1).unwrap().allocation(), [1]);
+
+        drop(acquire);
+        let state = shared.snapshot();
+        assert_eq!(state.available(), [2]);
+        assert_eq!(state.process(1).unwrap().allocation(), [0]);
+        assert!(shared.lock().settled.is_empty());
+    }
+
+    #[test]
+    fn woken_future_resolves_to_a_permit() {
+        let shared = pool();
+        let held = shared.acquire(0, &[2]).unwrap();
+        let flag = Arc::new(Flag::default());
+
+        let mut acquire = pin!(shared.acquire_async(1, &[1]));
+        assert!(poll_once(acquire.as_mut(), &flag).is_pending());
+        drop(held);
+        assert!(flag.0.load(Ordering::SeqCst));
+
+        let Poll::Ready(Ok(permit)) = poll_once(acquire.as_mut(), &flag) else {
+            panic!("the request was granted");
+        };
+        assert_eq!((permit.pid(), permit.amount()), (1, &[1][..]));
+        drop(permit);
+        assert_eq!(shared.snapshot().available(), [2]);
+    }
+
+    #[test]
+    fn resource_types_stay_put_while_permits_hold_units() {
+        let shared = SharedBanker::new(state(&[2], &[(&[2], &[2]), (&[0], &[2])]));
+        let flag = Arc::new(Flag::default());
+        let mut acquire = pin!(shared.acquire_async(1, &[1]));
+        assert!(poll_once(acquire.as_mut(), &flag).is_pending());
+
+        // The waiting request gains the new column.
+        assert_eq!(
+            shared.update(|banker| banker.add_resource_type(1, 0)),
+            Ok(1)
+        );
+        shared.update(|banker| banker.finish(0)).unwrap();
+        assert_eq!(
+            shared.update(|banker| banker.add_resource_type(1, 0)),
+            Err(BankersError::ResourceTypesLocked)
+        );
+
+        let Poll::Ready(Ok(permit)) = poll_once(acquire.as_mut(), &flag) else {
+            panic!("the request was granted");
+        };
+        assert_eq!(permit.amount(), [1, 0]);
+        assert_eq!(
+            shared.update(|banker| banker.retire_resource_type(1)),
+            Err(BankersError::ResourceTypesLocked)
+        );
+
+        drop(permit);
+        assert_eq!(shared.snapshot().available(), [2, 1]);
+        assert_eq!(
+            shared
+                .update(|banker| banker.retire_resource_type(1))
+                .map(|r| r.units),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot be released")]
+    fn permit_whose_units_were_released_elsewhere_panics() {
+        let shared = pool();
+        let permit = shared.acquire(0, &[1]).unwrap();
+        shared.update(|banker| banker.release(0, &[1])).unwrap();
+        drop(permit);
+    }
+
+    #[test]
+    fn future_is_woken_from_another_thread() {
+        let shared = pool();
+        let held = shared.acquire(0, &[2]).unwrap();
+
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| {
+                block_on(shared.acquire_async(1, &[2])).map(|permit| permit.amount().to_vec())
+            });
+            wait_for_pending(&shared, 1);
+            drop(held);
+            assert_eq!(waiter.join().unwrap(), Ok(vec![2]));
+        });
+        assert_eq!(shared.snapshot().available(), [2]);
+    }
+}
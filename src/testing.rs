@@ -0,0 +1,31 @@
+//! States shared by the unit tests.
+
+use crate::{BankersAlgorithm, Process};
+
+/// Builds a state from the totals and one `(allocation, max)` row per
+/// process, numbering the processes from 0.
+pub(crate) fn state(resources: &[u64], rows: &[(&[u64], &[u64])]) -> BankersAlgorithm {
+    let processes = rows
+        .iter()
+        .enumerate()
+        .map(|(pid, (allocation, max))| {
+            Process::new(pid, allocation.to_vec(), max.to_vec()).unwrap()
+        })
+        .collect();
+    BankersAlgorithm::from_parts(resources.to_vec(), processes).unwrap()
+}
+
+/// The five processes and three resource types of the textbook example,
+/// with 3, 3 and 2 units available.
+pub(crate) fn textbook() -> BankersAlgorithm {
+    state(
+        &[10, 5, 7],
+        &[
+            (&[0, 1, 0], &[7, 5, 3]),
+            (&[2, 0, 0], &[3, 2, 2]),
+            (&[3, 0, 2], &[9, 0, 2]),
+            (&[2, 1, 1], &[2, 2, 2]),
+            (&[0, 0, 2], &[4, 3, 3]),
+        ],
+    )
+}
@@ -0,0 +1,386 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use crate::{BankersAlgorithm, BankersError};
+
+/// A randomized workload to run against an initially idle system.
+///
+/// Processes arrive at random intervals with a random maximum claim, then
+/// alternate between requesting part of their remaining need, occasionally
+/// releasing part of what they hold, and working for a while, until they
+/// have made `requests_per_process` requests and finish. A request the
+/// banker refuses waits until some other process releases units.
+///
+/// The same seed always gives the same run. Event times that would
+/// overflow stay at `u64::MAX`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workload {
+    pub resources: Vec<u64>,
+    /// Number of processes that arrive over the run.
+    pub processes: usize,
+    /// Mean number of ticks between two arrivals.
+    pub interarrival: u64,
+    pub requests_per_process: usize,
+    /// Longest a process works between two of its steps, in ticks.
+    pub max_hold: u64,
+    pub seed: u64,
+}
+
+/// Resource utilization right after the state changed at `time`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtilizationSample {
+    pub time: u64,
+    /// Allocated share of each resource type, from 0 to 1.
+    pub utilization: Vec<f64>,
+}
+
+/// What happened during a [`Workload`] run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadReport {
+    /// Time of the last event.
+    pub duration: u64,
+    pub completed: usize,
+    /// Every time the banker was asked for units, retries included.
+    pub attempts: usize,
+    pub denials: usize,
+    pub granted: usize,
+    /// Ticks granted requests spent waiting, summed.
+    pub total_wait: u64,
+    pub utilization: Vec<UtilizationSample>,
+}
+
+impl WorkloadReport {
+    /// Completed processes per tick.
+    pub fn throughput(&self) -> f64 {
+        ratio(self.completed as f64, self.duration as f64)
+    }
+
+    /// Ticks a granted request waited on average.
+    pub fn average_wait(&self) -> f64 {
+        ratio(self.total_wait as f64, self.granted as f64)
+    }
+
+    /// Share of attempts the banker refused.
+    pub fn denial_rate(&self) -> f64 {
+        ratio(self.denials as f64, self.attempts as f64)
+    }
+
+    /// Utilization of each resource type averaged over the run, weighted by
+    /// how long each sample lasted.
+    pub fn average_utilization(&self) -> Vec<f64> {
+        self.utilization_between(0, self.duration)
+    }
+
+    /// Like [`average_utilization`](Self::average_utilization), over the
+    /// ticks from `start` up to `end` only.
+    pub fn utilization_between(&self, start: u64, end: u64) -> Vec<f64> {
+        let Some(first) = self.utilization.first() else {
+            return Vec::new();
+        };
+        let mut sums = vec![0.0; first.utilization.len()];
+        let ends = self.utilization[1..]
+            .iter()
+            .map(|next| next.time)
+            .chain([self.duration]);
+        for (sample, sample_end) in self.utilization.iter().zip(ends) {
+            let span = sample_end.min(end).saturating_sub(sample.time.max(start)) as f64;
+            for (sum, share) in sums.iter_mut().zip(&sample.utilization) {
+                *sum += share * span;
+            }
+        }
+        let span = end.saturating_sub(start) as f64;
+        sums.into_iter().map(|sum| ratio(sum, span)).collect()
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+/// xorshift64* seeded through splitmix64, so that any seed, 0 included,
+/// gives a usable stream.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in `0..=max`.
+    fn up_to(&mut self, max: u64) -> u64 {
+        match max.checked_add(1) {
+            Some(bound) => self.next_u64() % bound,
+            None => self.next_u64(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Event {
+    Arrival,
+    Step(usize),
+}
+
+struct Job {
+    requests_left: usize,
+}
+
+struct Waiting {
+    pid: usize,
+    amount: Vec<u64>,
+    since: u64,
+}
+
+struct Run {
+    banker: BankersAlgorithm,
+    rng: Rng,
+    events: BinaryHeap<Reverse<(u64, u64, Event)>>,
+    sequence: u64,
+    jobs: HashMap<usize, Job>,
+    waiting: VecDeque<Waiting>,
+    max_hold: u64,
+    report: WorkloadReport,
+}
+
+impl Workload {
+    pub fn new(resources: Vec<u64>, seed: u64) -> Workload {
+        Workload {
+            resources,
+            processes: 20,
+            interarrival: 3,
+            requests_per_process: 3,
+            max_hold: 5,
+            seed,
+        }
+    }
+
+    pub fn run(&self) -> Result<WorkloadReport, BankersError> {
+        let mut run = Run {
+            banker: BankersAlgorithm::from_parts(self.resources.clone(), Vec::new())?,
+            rng: Rng::new(self.seed),
+            events: BinaryHeap::new(),
+            sequence: 0,
+            jobs: HashMap::new(),
+            waiting: VecDeque::new(),
+            max_hold: self.max_hold,
+            report: WorkloadReport {
+                duration: 0,
+                completed: 0,
+                attempts: 0,
+                denials: 0,
+                granted: 0,
+                total_wait: 0,
+                utilization: Vec::new(),
+            },
+        };
+        run.sample(0);
+
+        let mut arrivals = 0;
+        let mut next_arrival = 0;
+        while arrivals < self.processes {
+            run.schedule(next_arrival, Event::Arrival);
+            let gap = run
+                .rng
+                .up_to(self.interarrival.saturating_sub(1).saturating_mul(2));
+            next_arrival = next_arrival.saturating_add(1).saturating_add(gap);
+            arrivals += 1;
+        }
+
+        while let Some(Reverse((time, _, event))) = run.events.pop() {
+            run.report.duration = time;
+            match event {
+                Event::Arrival => self.arrive(&mut run, time),
+                Event::Step(pid) => self.step(&mut run, time, pid),
+            }
+        }
+        Ok(run.report)
+    }
+
+    fn arrive(&self, run: &mut Run, time: u64) {
+        let max_need: Vec<u64> = self
+            .resources
+            .iter()
+            .map(|&total| run.rng.up_to(total))
+            .collect();
+        // The banker only ever holds safe states, and a safe state can always
+        // take a newcomer whose claim fits within the totals.
+        let pid = run
+            .banker
+            .admit(&max_need)
+            .expect("claims within the totals are admitted");
+        run.jobs.insert(
+            pid,
+            Job {
+                requests_left: self.requests_per_process,
+            },
+        );
+        run.schedule(time, Event::Step(pid));
+    }
+
+    fn step(&self, run: &mut Run, time: u64, pid: usize) {
+        let process = run.banker.process(pid).expect("scheduled process exists");
+        let holds = process.allocation().iter().any(|&units| units > 0);
+        let needs = process.need().iter().any(|&units| units > 0);
+        let job = &run.jobs[&pid];
+
+        if job.requests_left == 0 || !needs {
+            run.banker.finish(pid).expect("finished process exists");
+            run.jobs.remove(&pid);
+            run.report.completed += 1;
+            run.sample(time);
+            run.retry_waiting(time);
+        } else if holds && run.rng.up_to(3) == 0 {
+            let amount: Vec<u64> = process
+                .allocation()
+                .iter()
+                .map(|&units| run.rng.up_to(units))
+                .collect();
+            run.banker
+                .release(pid, &amount)
+                .expect("release stays within the allocation");
+            run.sample(time);
+            run.schedule_step(time, pid);
+            run.retry_waiting(time);
+        } else {
+            let need = process.need().to_vec();
+            let mut amount: Vec<u64> = need.iter().map(|&units| run.rng.up_to(units)).collect();
+            if amount.iter().all(|&units| units == 0) {
+                let k = need
+                    .iter()
+                    .position(|&units| units > 0)
+                    .expect("need is not empty");
+                amount[k] = 1;
+            }
+            run.jobs.get_mut(&pid).expect("job exists").requests_left -= 1;
+            if !run.try_request(time, pid, &amount, time) {
+                run.waiting.push_back(Waiting {
+                    pid,
+                    amount,
+                    since: time,
+                });
+            }
+        }
+    }
+}
+
+impl Run {
+    fn schedule(&mut self, time: u64, event: Event) {
+        self.events.push(Reverse((time, self.sequence, event)));
+        self.sequence += 1;
+    }
+
+    /// Lets process `pid` work for a random while before its next step.
+    fn schedule_step(&mut self, time: u64, pid: usize) {
+        let hold = self.rng.up_to(self.max_hold);
+        self.schedule(
+            time.saturating_add(1).saturating_add(hold),
+            Event::Step(pid),
+        );
+    }
+
+    fn sample(&mut self, time: u64) {
+        let utilization = self
+            .banker
+            .resources()
+            .iter()
+            .zip(self.banker.available())
+            .map(|(&total, &available)| ratio((total - available) as f64, total as f64))
+            .collect();
+        if let Some(last) = self.report.utilization.last_mut()
+            && last.time == time
+        {
+            last.utilization = utilization;
+        } else {
+            self.report
+                .utilization
+                .push(UtilizationSample { time, utilization });
+        }
+    }
+
+    /// Asks the banker for `amount`, scheduling the process's next step if
+    /// the request is granted.
+    fn try_request(&mut self, time: u64, pid: usize, amount: &[u64], since: u64) -> bool {
+        self.report.attempts += 1;
+        if self.banker.request(pid, amount).is_err() {
+            self.report.denials += 1;
+            return false;
+        }
+        self.report.granted += 1;
+        self.report.total_wait = self.report.total_wait.saturating_add(time - since);
+        self.sample(time);
+        self.schedule_step(time, pid);
+        true
+    }
+
+    /// Retries every waiting request in arrival order after units came back.
+    fn retry_waiting(&mut self, time: u64) {
+        for _ in 0..self.waiting.len() {
+            let waiting = self.waiting.pop_front().expect("queue is not empty");
+            if !self.try_request(time, waiting.pid, &waiting.amount, waiting.since) {
+                self.waiting.push_back(waiting);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_the_same_run() {
+        let workload = Workload::new(vec![10, 5, 7], 42);
+        let report = workload.run().unwrap();
+        assert_eq!(workload.run().unwrap(), report);
+
+        let other = Workload::new(vec![10, 5, 7], 43).run().unwrap();
+        assert_ne!(other, report);
+    }
+
+    #[test]
+    fn every_process_completes() {
+        for seed in 0..20 {
+            let mut workload = Workload::new(vec![4, 2], seed);
+            workload.processes = 30;
+            workload.requests_per_process = 5;
+            let report = workload.run().unwrap();
+
+            assert_eq!(report.completed, 30);
+            assert_eq!(report.attempts, report.granted + report.denials);
+            let last = report.utilization.last().unwrap();
+            assert_eq!(last.utilization, vec![0.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn extreme_timings_saturate() {
+        let mut workload = Workload::new(vec![10, 5], 7);
+        workload.interarrival = u64::MAX;
+        workload.max_hold = u64::MAX;
+        let report = workload.run().unwrap();
+        assert_eq!(report.completed, 20);
+        assert_eq!(report.duration, u64::MAX);
+    }
+
+    #[test]
+    fn rejects_an_empty_system() {
+        assert_eq!(
+            Workload::new(Vec::new(), 0).run(),
+            Err(BankersError::NoResources)
+        );
+    }
+}